@@ -0,0 +1,153 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! Derive macros for [`serde-scale`](https://docs.rs/serde-scale)
+//!
+//! Use them through the `derive` feature of `serde-scale`.
+
+#![deny(warnings)]
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    meta::ParseNestedMeta, parse_macro_input, Data, DeriveInput, Expr, Field, Fields, LitStr,
+    Token,
+};
+
+/// Implements `ScaleLayout` for a structure, listing its fields in serialization order
+///
+/// The field order can be pinned with `#[scale_layout(pinned("a", "b"))]`, in which case
+/// compilation fails if the fields are not exactly the pinned ones in the same order.
+///
+/// Fields marked `#[serde(skip)]` or `#[serde(skip_serializing)]` are not listed, and fields
+/// marked `#[serde(rename = "...")]` are listed under their new name. Attributes making the
+/// encoding depend on the value or differ between serialization and deserialization
+/// (`skip_deserializing`, `skip_serializing_if`, `flatten`) and `rename_all` are rejected.
+#[proc_macro_derive(ScaleLayout, attributes(scale_layout))]
+pub fn derive_scale_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(s) => {
+            reject_rename_all(&input)?;
+            field_names(&s.fields)?
+        }
+        Data::Enum(_) | Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "ScaleLayout can only be derived for structures",
+            ));
+        }
+    };
+    if let Some(pinned) = pinned_fields(&input)? {
+        let pinned_names = pinned.iter().map(LitStr::value).collect::<Vec<_>>();
+        if pinned_names != fields {
+            let span = pinned.first().map_or_else(|| input.ident.span(), |f| f.span());
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "Fields of {} are [{}] but the pinned layout is [{}]",
+                    input.ident,
+                    fields.join(", "),
+                    pinned_names.join(", "),
+                ),
+            ));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::serde_scale::ScaleLayout for #name #ty_generics #where_clause {
+            const FIELDS: &'static [&'static str] = &[#(#fields),*];
+        }
+    })
+}
+
+fn field_names(fields: &Fields) -> syn::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for (i, f) in fields.iter().enumerate() {
+        let name = f.ident.as_ref().map_or_else(|| i.to_string(), ToString::to_string);
+        if let Some(name) = serialized_name(f, name)? {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Returns the name under which `serde` serializes a field, or `None` if it is skipped
+fn serialized_name(field: &Field, mut name: String) -> syn::Result<Option<String>> {
+    let mut skipped = false;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                skipped = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                if meta.input.peek(Token![=]) {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    return Ok(());
+                }
+                meta.parse_nested_meta(|meta| {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    if meta.path.is_ident("serialize") {
+                        name = value.value();
+                    }
+                    Ok(())
+                })
+            } else if ["skip_deserializing", "skip_serializing_if", "flatten"]
+                .iter()
+                .any(|unsupported| meta.path.is_ident(unsupported))
+            {
+                Err(meta.error("ScaleLayout does not support this serde attribute"))
+            } else {
+                skip_meta_value(&meta)
+            }
+        })?;
+    }
+    Ok(if skipped { None } else { Some(name) })
+}
+
+fn reject_rename_all(input: &DeriveInput) -> syn::Result<()> {
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                Err(meta.error("ScaleLayout does not support rename_all; rename fields instead"))
+            } else {
+                skip_meta_value(&meta)
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Consumes the value of a `serde` attribute that does not affect the layout
+fn skip_meta_value(meta: &ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let _content;
+        syn::parenthesized!(_content in meta.input);
+    }
+    Ok(())
+}
+
+fn pinned_fields(input: &DeriveInput) -> syn::Result<Option<Vec<LitStr>>> {
+    let mut pinned = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("scale_layout")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pinned") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let names = content.parse_terminated(|i| i.parse::<LitStr>(), Token![,])?;
+                pinned = Some(names.into_iter().collect());
+                Ok(())
+            } else {
+                Err(meta.error("Unsupported scale_layout attribute"))
+            }
+        })?;
+    }
+    Ok(pinned)
+}
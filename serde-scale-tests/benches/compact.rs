@@ -0,0 +1,93 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Serialize;
+use serde_scale::{Compact, Serializer};
+
+/// Large-value path of the previous encoder, which located the last non-zero byte with an iterator
+fn encode_large_iter(v: u64, out: &mut Vec<u8>) {
+    let mut bytes = [0u8; 9];
+    let mut rest = v;
+    let src = std::iter::from_fn(|| {
+        if rest == 0 {
+            return None;
+        }
+        let low = (rest & 0xff) as u8;
+        rest >>= 8;
+        Some(low)
+    });
+    let end = bytes
+        .iter_mut()
+        .skip(1)
+        .zip(src)
+        .enumerate()
+        .map(|(i, (dst, src))| {
+            *dst = src;
+            i
+        })
+        .last()
+        .unwrap()
+        + 1;
+    bytes[0] = (((end - 4) as u8) << 2) | 0x3;
+    out.extend_from_slice(&bytes[..end + 1]);
+}
+
+/// Large-value path of the current encoder
+fn encode_large_leading_zeros(v: u64, out: &mut Vec<u8>) {
+    let len = 8 - (v.leading_zeros() / 8) as usize;
+    let mut bytes = [0u8; 9];
+    bytes[0] = (((len - 4) as u8) << 2) | 0x3;
+    bytes[1..=len].copy_from_slice(&v.to_le_bytes()[..len]);
+    out.extend_from_slice(&bytes[..=len]);
+}
+
+fn large_values() -> Vec<u64> {
+    (30..64).map(|shift| (1u64 << shift) | 0x55).collect()
+}
+
+fn serialize_large_compact(c: &mut Criterion) {
+    let values = large_values();
+    for &v in &values {
+        let (mut old, mut new) = (Vec::new(), Vec::new());
+        encode_large_iter(v, &mut old);
+        encode_large_leading_zeros(v, &mut new);
+        assert_eq!(old, new);
+        assert_eq!(new, serde_scale::to_vec(&Compact(v)).unwrap());
+    }
+    let mut group = c.benchmark_group("large compact");
+    group.bench_function("iterator", |b| {
+        let mut out = Vec::with_capacity(512);
+        b.iter(|| {
+            out.clear();
+            for &v in black_box(&values) {
+                encode_large_iter(v, &mut out);
+            }
+            out.len()
+        })
+    });
+    group.bench_function("leading_zeros", |b| {
+        let mut out = Vec::with_capacity(512);
+        b.iter(|| {
+            out.clear();
+            for &v in black_box(&values) {
+                encode_large_leading_zeros(v, &mut out);
+            }
+            out.len()
+        })
+    });
+    group.bench_function("serializer", |b| {
+        let mut out = Vec::with_capacity(512);
+        b.iter(|| {
+            out.clear();
+            let mut serializer = Serializer::new(&mut out);
+            for &v in black_box(&values) {
+                Compact(v).serialize(&mut serializer).unwrap();
+            }
+            serializer.bytes_written()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serialize_large_compact);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Small {
+    id: u32,
+    name: &'static str,
+    tags: [u16; 4],
+}
+
+fn serialize_small(c: &mut Criterion) {
+    let value = Small { id: 7, name: "a short name", tags: [1, 2, 3, 4] };
+    let mut group = c.benchmark_group("small value");
+    group.bench_function("to_vec", |b| b.iter(|| serde_scale::to_vec(black_box(&value)).unwrap()));
+    group.bench_function("to_vec_pooled", |b| {
+        b.iter(|| serde_scale::to_vec_pooled(black_box(&value)).unwrap())
+    });
+    group.finish();
+}
+
+fn serialize_large(c: &mut Criterion) {
+    let value = (0..10_000u32).collect::<Vec<_>>();
+    let mut group = c.benchmark_group("10k u32");
+    group.bench_function("to_vec", |b| b.iter(|| serde_scale::to_vec(black_box(&value)).unwrap()));
+    group.bench_function("to_vec_with_capacity sized", |b| {
+        b.iter(|| {
+            let value = black_box(&value);
+            let size = serde_scale::serialized_size(value).unwrap();
+            serde_scale::to_vec_with_capacity(value, size).unwrap()
+        })
+    });
+    group.bench_function("to_vec_pooled", |b| {
+        b.iter(|| serde_scale::to_vec_pooled(black_box(&value)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serialize_small, serialize_large);
+criterion_main!(benches);
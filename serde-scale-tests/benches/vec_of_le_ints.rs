@@ -0,0 +1,37 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Bulk32(#[serde(with = "serde_scale::vec_of_le_ints")] Vec<u32>);
+
+#[derive(Deserialize)]
+struct Bulk64(#[serde(with = "serde_scale::vec_of_le_ints")] Vec<u64>);
+
+fn deserialize_vec_u32(c: &mut Criterion) {
+    let encoded = serde_scale::to_vec(&(0..10_000).collect::<Vec<u32>>()).unwrap();
+    let mut group = c.benchmark_group("Vec<u32>");
+    group.bench_function("generic", |b| {
+        b.iter(|| serde_scale::from_slice::<Vec<u32>>(black_box(&encoded)).unwrap())
+    });
+    group.bench_function("vec_of_le_ints", |b| {
+        b.iter(|| serde_scale::from_slice::<Bulk32>(black_box(&encoded)).unwrap().0)
+    });
+    group.finish();
+}
+
+fn deserialize_vec_u64(c: &mut Criterion) {
+    let encoded = serde_scale::to_vec(&(0..10_000).collect::<Vec<u64>>()).unwrap();
+    let mut group = c.benchmark_group("Vec<u64>");
+    group.bench_function("generic", |b| {
+        b.iter(|| serde_scale::from_slice::<Vec<u64>>(black_box(&encoded)).unwrap())
+    });
+    group.bench_function("vec_of_le_ints", |b| {
+        b.iter(|| serde_scale::from_slice::<Bulk64>(black_box(&encoded)).unwrap().0)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deserialize_vec_u32, deserialize_vec_u64);
+criterion_main!(benches);
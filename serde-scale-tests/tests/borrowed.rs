@@ -0,0 +1,76 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::Deserialize;
+use serde_scale::{ClosureReader, Deserializer, EndOfInput, VecReader};
+use std::borrow::Cow;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Record<'a> {
+    name: &'a str,
+    payload: &'a [u8],
+}
+
+#[test]
+fn borrowed_fields_point_into_input() {
+    let bytes = serde_scale::to_vec(&("name", b"data".to_vec())).unwrap();
+    let record = serde_scale::from_slice::<Record<'_>>(&bytes).unwrap();
+    assert_eq!(record, Record { name: "name", payload: b"data" });
+    let range = bytes.as_ptr_range();
+    assert!(range.contains(&record.name.as_ptr()));
+    assert!(range.contains(&record.payload.as_ptr()));
+}
+
+#[test]
+fn borrowed_value_lives_as_long_as_buffer() {
+    let bytes = serde_scale::to_vec(&"hello").unwrap();
+    let s = {
+        let input = &bytes[..];
+        serde_scale::from_slice::<&str>(input).unwrap()
+    };
+    assert_eq!(s, "hello");
+}
+
+#[test]
+fn borrowed_str_points_into_vec() {
+    let bytes: Vec<u8> = serde_scale::to_vec(&"in a vec").unwrap();
+    let s = serde_scale::from_slice::<&str>(&bytes).unwrap();
+    assert_eq!(s, "in a vec");
+    assert!(bytes.as_ptr_range().contains(&s.as_ptr()));
+    let s = <&str>::deserialize(&mut Deserializer::new(&bytes[..])).unwrap();
+    assert!(bytes.as_ptr_range().contains(&s.as_ptr()));
+    let s = <&str>::deserialize(&mut Deserializer::new(VecReader::from(&bytes))).unwrap();
+    assert!(bytes.as_ptr_range().contains(&s.as_ptr()));
+}
+
+#[derive(Debug, Deserialize)]
+struct Named<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    id: u8,
+}
+
+#[test]
+fn cow_borrows_from_slice() {
+    let bytes = serde_scale::to_vec(&("name", 3u8)).unwrap();
+    let named = serde_scale::from_slice::<Named<'_>>(&bytes).unwrap();
+    assert!(matches!(named.name, Cow::Borrowed("name")));
+    assert_eq!(named.id, 3);
+}
+
+#[test]
+fn cow_is_owned_when_read_from_stream() {
+    let bytes = serde_scale::to_vec(&("name", 3u8)).unwrap();
+    let mut input = &bytes[..];
+    let reader = ClosureReader::new(|buf: &mut [u8]| {
+        if buf.len() > input.len() {
+            return Err(EndOfInput);
+        }
+        let (head, tail) = input.split_at(buf.len());
+        buf.copy_from_slice(head);
+        input = tail;
+        Ok(())
+    });
+    let named = Named::deserialize(&mut Deserializer::new(reader)).unwrap();
+    assert!(matches!(&named.name, Cow::Owned(s) if s == "name"));
+    assert_eq!(named.id, 3);
+}
@@ -0,0 +1,90 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_scale::{BytesReader, BytesWriter, Deserializer, EndOfInput, Error, Serializer};
+use std::fmt::Debug;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Packet {
+    id: u32,
+    name: String,
+    payload: Vec<u8>,
+    tail: Option<u64>,
+}
+
+fn packet() -> Packet {
+    Packet { id: 0x0102_0304, name: "chained".into(), payload: vec![0xab; 40], tail: Some(9) }
+}
+
+/// Splits `bytes` into a chain of `Bytes` cut at each of `cuts`
+fn chained(bytes: &[u8], cuts: &[usize]) -> Box<dyn Buf> {
+    let mut buf: Box<dyn Buf> = Box::new(Bytes::new());
+    let mut start = 0;
+    for &end in cuts.iter().chain([bytes.len()].iter()) {
+        buf = Box::new(buf.chain(Bytes::copy_from_slice(&bytes[start..end])));
+        start = end;
+    }
+    buf
+}
+
+fn from_buf<T: DeserializeOwned>(buf: impl Buf) -> Result<T, Error<EndOfInput>> {
+    T::deserialize(&mut Deserializer::new(BytesReader::new(buf)))
+}
+
+fn assert_reads_back<T>(value: &T)
+where
+    T: Debug + DeserializeOwned + PartialEq + Serialize,
+{
+    let bytes = serde_scale::to_vec(value).unwrap();
+    for cut in 0..=bytes.len() {
+        assert_eq!(from_buf::<T>(chained(&bytes, &[cut])).unwrap(), *value, "Cut at {}", cut);
+    }
+}
+
+#[test]
+fn value_reads_from_single_chunk() {
+    let bytes = serde_scale::to_vec(&packet()).unwrap();
+    assert_eq!(from_buf::<Packet>(Bytes::from(bytes)).unwrap(), packet());
+}
+
+#[test]
+fn value_reads_across_chunk_boundaries() {
+    assert_reads_back(&packet());
+    assert_reads_back(&(u64::MAX, String::from("\u{1f600}"), [1u16, 2, 3]));
+}
+
+#[test]
+fn value_reads_from_many_small_chunks() {
+    let bytes = serde_scale::to_vec(&packet()).unwrap();
+    let cuts = (1..bytes.len()).collect::<Vec<_>>();
+    assert_eq!(from_buf::<Packet>(chained(&bytes, &cuts)).unwrap(), packet());
+}
+
+#[test]
+fn buffer_is_advanced_past_value() {
+    let mut bytes = serde_scale::to_vec(&packet()).unwrap();
+    bytes.extend([7, 8]);
+    let mut deserializer = Deserializer::new(BytesReader::new(chained(&bytes, &[10])));
+    assert_eq!(Packet::deserialize(&mut deserializer).unwrap(), packet());
+    assert_eq!(deserializer.into_inner().into_inner().chunk(), [7, 8]);
+}
+
+#[test]
+fn truncated_chain_is_end_of_input() {
+    let bytes = serde_scale::to_vec(&packet()).unwrap();
+    let res = from_buf::<Packet>(chained(&bytes[..bytes.len() - 1], &[5]));
+    assert!(matches!(res, Err(Error::Io(EndOfInput))));
+}
+
+#[test]
+fn value_roundtrips_through_bytes_mut() {
+    let mut buf = BytesMut::new();
+    buf.put_u8(0xff);
+    let mut serializer = Serializer::new(BytesWriter::new(&mut buf));
+    packet().serialize(&mut serializer).unwrap();
+    assert_eq!(serializer.bytes_written(), serde_scale::to_vec(&packet()).unwrap().len());
+    let mut frozen = buf.freeze();
+    assert_eq!(frozen.get_u8(), 0xff);
+    assert_eq!(from_buf::<Packet>(frozen).unwrap(), packet());
+}
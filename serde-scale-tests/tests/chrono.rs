@@ -0,0 +1,53 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Record {
+    #[serde(with = "serde_scale::chrono")]
+    at: DateTime<Utc>,
+    tag: u8,
+}
+
+#[test]
+fn date_time_serializes_as_millis_since_epoch() {
+    let at = DateTime::from_timestamp(1_600_000_000, 123_000_000).unwrap();
+    let bytes = serde_scale::to_vec(&Record { at, tag: 7 }).unwrap();
+    let mut expected = 1_600_000_000_123i64.to_le_bytes().to_vec();
+    expected.push(7);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn date_time_roundtrips() {
+    let times = [
+        DateTime::UNIX_EPOCH,
+        DateTime::from_timestamp_millis(1_600_000_000_123).unwrap(),
+        DateTime::from_timestamp_millis(-1_500).unwrap(),
+        DateTime::<Utc>::MAX_UTC - TimeDelta::nanoseconds(999_999),
+        DateTime::<Utc>::MIN_UTC,
+    ];
+    for at in times {
+        let record = Record { at, tag: 1 };
+        let bytes = serde_scale::to_vec(&record).unwrap();
+        assert_eq!(serde_scale::from_slice::<Record>(&bytes).unwrap(), record);
+    }
+}
+
+#[test]
+fn sub_millisecond_precision_rounds_towards_past() {
+    let at = DateTime::from_timestamp(-2, 998_500_000).unwrap();
+    let bytes = serde_scale::to_vec(&Record { at, tag: 0 }).unwrap();
+    assert_eq!(bytes[..8], (-1_002i64).to_le_bytes());
+    let at = DateTime::from_timestamp(1, 999_999).unwrap();
+    let bytes = serde_scale::to_vec(&Record { at, tag: 0 }).unwrap();
+    assert_eq!(bytes[..8], 1_000i64.to_le_bytes());
+}
+
+#[test]
+fn out_of_range_time_fails_to_deserialize() {
+    let mut bytes = i64::MAX.to_le_bytes().to_vec();
+    bytes.push(0);
+    assert!(serde_scale::from_slice::<Record>(&bytes).is_err());
+}
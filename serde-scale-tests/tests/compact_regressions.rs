@@ -0,0 +1,107 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! Inputs exercising edge cases of the compact decoder, each expected to produce a defined result
+
+use serde_scale::{Compact, EndOfInput, Error};
+
+fn decode(bytes: &[u8]) -> Result<u64, Error<EndOfInput>> {
+    serde_scale::from_slice::<Compact<u64>>(bytes).map(|c| c.0)
+}
+
+fn assert_eof(bytes: &[u8]) {
+    assert!(matches!(decode(bytes), Err(Error::Io(EndOfInput))), "{:?}", bytes);
+}
+
+fn assert_too_large(bytes: &[u8]) {
+    assert!(
+        matches!(decode(bytes), Err(Error::CollectionTooLargeToDeserialize)),
+        "{:?}",
+        bytes,
+    );
+}
+
+#[test]
+fn empty_input_is_end_of_input() {
+    assert_eof(&[]);
+}
+
+#[test]
+fn single_byte_mode() {
+    assert_eq!(decode(&[0x00]).unwrap(), 0);
+    assert_eq!(decode(&[0xfc]).unwrap(), 63);
+}
+
+#[test]
+fn two_byte_mode() {
+    assert_eq!(decode(&[0x01, 0x00]).unwrap(), 0);
+    assert_eq!(decode(&[0x01, 0x01]).unwrap(), 64);
+    assert_eq!(decode(&[0xfd, 0xff]).unwrap(), 0x3fff);
+    assert_eof(&[0x01]);
+    assert_eof(&[0xfd]);
+}
+
+#[test]
+fn four_byte_mode() {
+    assert_eq!(decode(&[0x02, 0x00, 0x00, 0x00]).unwrap(), 0);
+    assert_eq!(decode(&[0x02, 0x00, 0x01, 0x00]).unwrap(), 0x4000);
+    assert_eq!(decode(&[0xfe, 0xff, 0xff, 0xff]).unwrap(), 0x3fff_ffff);
+    assert_eof(&[0x02]);
+    assert_eof(&[0x02, 0x00, 0x00]);
+}
+
+#[test]
+fn big_integer_mode() {
+    assert_eq!(decode(&[0x03, 0x00, 0x00, 0x00, 0x40]).unwrap(), 0x4000_0000);
+    assert_eq!(decode(&[0x03, 0xff, 0xff, 0xff, 0xff]).unwrap(), u64::from(u32::MAX));
+    assert_eq!(decode(&[0x13, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).unwrap(), u64::MAX);
+    assert_eof(&[0x03]);
+    assert_eof(&[0x03, 0x00, 0x00, 0x00]);
+    assert_eof(&[0x13, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn big_integers_overflowing_target_are_rejected() {
+    assert!(matches!(decode(&[0x17, 0, 0, 0, 0, 0, 0, 0, 0, 1]), Err(Error::Other(_))));
+    let mut max = [0xff; 17];
+    max[0] = 0x33;
+    assert!(matches!(decode(&max), Err(Error::Other(_))));
+}
+
+#[test]
+fn big_integers_up_to_128_bits_decode_into_u128() {
+    let mut max = [0xff; 17];
+    max[0] = 0x33;
+    assert_eq!(serde_scale::from_slice::<Compact<u128>>(&max).unwrap(), Compact(u128::MAX));
+    let two_pow_64 = [0x17, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+    assert_eq!(serde_scale::from_slice::<Compact<u128>>(&two_pow_64).unwrap(), Compact(1 << 64));
+}
+
+#[test]
+fn big_integers_wider_than_128_bits_are_rejected() {
+    assert_too_large(&[0x37]);
+    assert_too_large(&[0xff]);
+}
+
+#[test]
+fn wide_encodings_of_small_values_are_accepted() {
+    let mut bytes = [0; 17];
+    bytes[0] = 0x33;
+    bytes[1] = 5;
+    assert_eq!(decode(&bytes).unwrap(), 5);
+}
+
+#[test]
+fn compact_lengths_in_collections_are_checked() {
+    assert!(matches!(
+        serde_scale::from_slice::<Vec<u8>>(&[0xfd]),
+        Err(Error::Io(EndOfInput)),
+    ));
+    assert!(matches!(
+        serde_scale::from_slice::<Vec<u8>>(&[0xfe, 0xff, 0xff, 0xff]),
+        Err(Error::Io(EndOfInput)),
+    ));
+    assert!(matches!(
+        serde_scale::from_slice::<String>(&[0xff]),
+        Err(Error::CollectionTooLargeToDeserialize),
+    ));
+}
@@ -0,0 +1,6 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+#[test]
+fn compile_fail() {
+    trybuild::TestCases::new().compile_fail("tests/compile_fail/*.rs");
+}
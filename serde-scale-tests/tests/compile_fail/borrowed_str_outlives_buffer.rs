@@ -0,0 +1,7 @@
+fn main() {
+    let s: &str = {
+        let bytes = serde_scale::to_vec(&"borrowed").unwrap();
+        serde_scale::from_slice(&bytes).unwrap()
+    };
+    println!("{}", s);
+}
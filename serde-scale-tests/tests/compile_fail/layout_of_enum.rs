@@ -0,0 +1,8 @@
+use serde_scale::ScaleLayout;
+
+#[derive(ScaleLayout)]
+enum Shape {
+    Circle,
+}
+
+fn main() {}
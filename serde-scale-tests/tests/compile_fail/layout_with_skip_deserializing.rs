@@ -0,0 +1,11 @@
+use serde::Serialize;
+use serde_scale::ScaleLayout;
+
+#[derive(ScaleLayout, Serialize)]
+struct Record {
+    id: u32,
+    #[serde(skip_deserializing)]
+    cached: u8,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use serde_scale::ScaleLayout;
+
+#[derive(ScaleLayout)]
+#[scale_layout(pinned("id", "name"))]
+struct User {
+    name: String,
+    id: u32,
+}
+
+fn main() {}
@@ -1,10 +1,15 @@
 // Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
 
-use parity_scale_codec::{Encode, OptionBool};
+use either::Either;
+use parity_scale_codec::{Compact as CodecCompact, Encode, OptionBool};
+use serde_scale::{Compact, Nested, RawBytes};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
     error::Error,
     fmt::Debug,
+    num::{Saturating, Wrapping},
 };
 
 fn roundtrips<T>(v: &T) -> Result<(), Box<dyn Error>>
@@ -119,30 +124,54 @@ impl Test for SameAsCodec {
 
 fn apply_test<T: Test>(test: T) {
     let results = vec![
-        test.run(i8::min_value()),
+        test.run(i8::MIN),
         test.run(1_i8),
-        test.run(i8::max_value()),
-        test.run(i16::min_value()),
+        test.run(i8::MAX),
+        test.run(i16::MIN),
         test.run(1_i8),
-        test.run(i16::max_value()),
-        test.run(i32::min_value()),
+        test.run(i16::MAX),
+        test.run(i32::MIN),
         test.run(1_i32),
-        test.run(i32::max_value()),
-        test.run(i64::min_value()),
+        test.run(i32::MAX),
+        test.run(i64::MIN),
         test.run(1_i64),
-        test.run(i64::max_value()),
-        test.run(u8::min_value()),
+        test.run(i64::MAX),
+        test.run(u8::MIN),
         test.run(1_u8),
-        test.run(u8::max_value()),
-        test.run(u16::min_value()),
+        test.run(u8::MAX),
+        test.run(u16::MIN),
         test.run(1_u16),
-        test.run(u16::max_value()),
-        test.run(u32::min_value()),
+        test.run(u16::MAX),
+        test.run(u32::MIN),
         test.run(1_u32),
-        test.run(u32::max_value()),
-        test.run(u64::min_value()),
+        test.run(u32::MAX),
+        test.run(u64::MIN),
         test.run(1_u64),
-        test.run(u64::max_value()),
+        test.run(u64::MAX),
+        test.run(u128::MIN),
+        test.run(1_u128 << 64),
+        test.run(u128::MAX),
+        test.run(i128::MIN),
+        test.run(-1_i128),
+        test.run(i128::MAX),
+        test.run_with('a', 'a' as u32),
+        test.run_with('\u{1d11e}', 0x1d11e_u32),
+        test.run_with('\u{1f600}', 0x1f600_u32),
+        test.run_with(Wrapping(u32::MAX), u32::MAX),
+        test.run_with(Saturating(u64::MAX), u64::MAX),
+        test.run_with(Compact(0u32), CodecCompact(0u32)),
+        test.run_with(Compact(0x3f_u32), CodecCompact(0x3f_u32)),
+        test.run_with(Compact(0x3fff_u32), CodecCompact(0x3fff_u32)),
+        test.run_with(Compact(0x3fff_ffff_u32), CodecCompact(0x3fff_ffff_u32)),
+        test.run_with(Compact(0x4000_0000_u32), CodecCompact(0x4000_0000_u32)),
+        test.run_with(Compact(u32::MAX), CodecCompact(u32::MAX)),
+        test.run_with(Compact(1_u64 << 32), CodecCompact(1_u64 << 32)),
+        test.run_with(Compact(u64::MAX), CodecCompact(u64::MAX)),
+        test.run_with(Compact(u8::MAX), CodecCompact(u8::MAX)),
+        test.run_with(Compact(u16::MAX), CodecCompact(u16::MAX)),
+        test.run_with(Compact(1_u128 << 64), CodecCompact(1_u128 << 64)),
+        test.run_with(Compact(u128::MAX), CodecCompact(u128::MAX)),
+        test.run(CompactFields { a: 63, b: 0x3fff, c: 0x4000_0000, d: u64::MAX, e: u128::MAX }),
         test.run(false),
         test.run(true),
         test.run(None::<i32>),
@@ -150,25 +179,65 @@ fn apply_test<T: Test>(test: T) {
         test.run_with(None::<bool>, OptionBool(None)),
         test.run_with(Some(false), OptionBool(Some(false))),
         test.run_with(Some(true), OptionBool(Some(true))),
+        test.run(Ok::<bool, u8>(true)),
+        test.run(Ok::<bool, u8>(false)),
+        test.run(Err::<bool, u8>(7)),
+        test.run_with(
+            vec![None, Some(true), Some(false), None, Some(true)],
+            vec![
+                OptionBool(None),
+                OptionBool(Some(true)),
+                OptionBool(Some(false)),
+                OptionBool(None),
+                OptionBool(Some(true)),
+            ],
+        ),
+        test.run_with((true, None::<bool>), (true, OptionBool(None))),
+        test.run_with((false, Some(false)), (false, OptionBool(Some(false)))),
+        test.run_with(Either::<u8, String>::Left(3), EitherLike::Left(3)),
+        test.run_with(Either::<u8, String>::Right("foo".into()), EitherLike::Right("foo".into())),
         test.run(Ok::<i32, String>(3)),
         test.run(Err::<String, i32>(3)),
         test.run(vec![1, 2, 3]),
         test.run(String::from("foo")),
         test.run((3, String::from("foo"))),
+        test.run(String::from("h\u{e9}llo \u{1f600} \u{4e16}\u{754c}")),
+        test.run(Msg { head: vec![0xc3], body: "\u{1f600}".repeat(20) }),
+        test.run(Pair { a: u16::MIN, b: i16::MIN }),
+        test.run(Pair { a: u16::MAX, b: i16::MAX }),
+        test.run(vec![u16::MIN, 1, u16::MAX]),
+        test.run(Msg { head: vec![], body: "body".into() }),
+        test.run(Msg { head: vec![1, 2, 3], body: String::new() }),
+        test.run(Msg { head: vec![], body: String::new() }),
+        test.run(BTreeMap::<u8, u32>::new()),
+        test.run(map_of_pairs()),
         test.run(Operator { name: "+".into(), priority: 2 }),
+        test.run(Some(Operator { name: "*".into(), priority: 3 })),
+        test.run(None::<Operator>),
+        test.run_with(Reverse(0x0102_0304_u32), 0x0102_0304_u32),
         test.run(Expression::Const(3)),
         test.run(Expression::Op(
             Box::new(Expression::Const(2)),
             Operator { name: "+".into(), priority: 2 },
             Box::new(Expression::Const(3)),
         )),
+        test.run(mixed_expressions()),
+        test.run_with(Nested(Pair { a: 1, b: -1 }), Pair { a: 1, b: -1 }.encode()),
+        test.run_with(Nested(map_of_pairs()), map_of_pairs().encode()),
+        test.run_with(Nested(Nested(7u8)), 7u8.encode().encode()),
+        test.run(Node { value: 1, next: None }),
+        test.run(linked_list(&[1, 2, 3, u32::MAX])),
+        test.run(Shape::Rect { w: 3, h: 0x0102_0304 }),
+        test.run(vec![Shape::Circle { r: 1 }, Shape::Rect { w: u32::MAX, h: 0 }]),
+        test.run(MixedSigns { a: i8::MIN, b: u16::MAX, c: i32::MIN, d: u64::MAX }),
+        test.run(MixedSigns { a: i8::MAX, b: 0, c: -1, d: 0 }),
     ];
     let error_msg = results
         .into_iter()
         .flat_map(|r| r.err())
         .map(|e| format!("\n{}\n", e))
         .collect::<String>();
-    assert!(error_msg.is_empty(), error_msg);
+    assert!(error_msg.is_empty(), "{}", error_msg);
 }
 
 #[test]
@@ -181,6 +250,604 @@ fn results_match_codec() {
     apply_test(SameAsCodec);
 }
 
+#[test]
+fn map_serializes_like_vec_of_pairs() {
+    let map = map_of_pairs();
+    let pairs = map.clone().into_iter().collect::<Vec<_>>();
+    let map_out = serde_scale::to_vec(&map).unwrap();
+    assert_eq!(map_out, serde_scale::to_vec(&pairs).unwrap());
+    assert_eq!(map_out, pairs.encode());
+    assert_eq!(map_out, map.encode());
+}
+
+#[test]
+fn map_entries_have_no_separator() {
+    let map = (0..3u32).map(|k| (k, k * 0x0101)).collect::<BTreeMap<u32, u32>>();
+    let pairs = map.clone().into_iter().collect::<Vec<_>>();
+    same_as_codec(&map, &pairs).unwrap();
+    let out = serde_scale::to_vec(&map).unwrap();
+    assert_eq!(out.len(), 1 + 3 * 8);
+    assert_eq!(out[..9], [12, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(serde_scale::from_slice::<BTreeMap<u32, u32>>(&out).unwrap(), map);
+}
+
+#[test]
+fn struct_variant_fields_follow_discriminant() {
+    let rect = Shape::Rect { w: 2, h: 3 };
+    let out = serde_scale::to_vec(&rect).unwrap();
+    assert_eq!(out, [1, 2, 0, 0, 0, 3, 0, 0, 0]);
+    assert_eq!(serde_scale::from_slice::<Shape>(&out).unwrap(), rect);
+}
+
+#[test]
+fn map_entries_decode_key_then_value() {
+    let map = [(1u32, "one"), (20, ""), (300, "three hundred"), (u32::MAX, "max")]
+        .iter()
+        .map(|&(k, v)| (k, v.to_owned()))
+        .collect::<BTreeMap<u32, String>>();
+    same_as_codec(&map, &map).unwrap();
+    let out = serde_scale::to_vec(&map).unwrap();
+    assert_eq!(out[..9], [16, 1, 0, 0, 0, 12, b'o', b'n', b'e']);
+    assert_eq!(serde_scale::from_slice::<BTreeMap<u32, String>>(&out).unwrap(), map);
+    let pairs = serde_scale::from_slice::<Vec<(u32, String)>>(&out).unwrap();
+    assert_eq!(pairs, map.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn linked_list_nests_options() {
+    let out = serde_scale::to_vec(&linked_list(&[1, 2])).unwrap();
+    assert_eq!(out, [1, 0, 0, 0, 1, 2, 0, 0, 0, 0]);
+    let list = linked_list(&(0..100).collect::<Vec<_>>());
+    let out = serde_scale::to_vec(&list).unwrap();
+    assert_eq!(out.len(), 100 * 5);
+    assert_eq!(serde_scale::from_slice::<Node>(&out).unwrap(), list);
+}
+
+#[test]
+fn primitive_matrix_matches_codec() {
+    let min = Primitives {
+        flag: false,
+        i8: i8::MIN,
+        i16: i16::MIN,
+        i32: i32::MIN,
+        i64: i64::MIN,
+        i128: i128::MIN,
+        u8: u8::MIN,
+        u16: u16::MIN,
+        u32: u32::MIN,
+        u64: u64::MIN,
+        u128: u128::MIN,
+        ch: '\0',
+        text: String::new(),
+        blob: vec![],
+        maybe: None,
+        shape: Shape::Circle { r: 0 },
+    };
+    let max = Primitives {
+        flag: true,
+        i8: i8::MAX,
+        i16: i16::MAX,
+        i32: i32::MAX,
+        i64: i64::MAX,
+        i128: i128::MAX,
+        u8: u8::MAX,
+        u16: u16::MAX,
+        u32: u32::MAX,
+        u64: u64::MAX,
+        u128: u128::MAX,
+        ch: char::MAX,
+        text: "\u{10ffff}".repeat(16),
+        blob: vec![0xff; 64],
+        maybe: Some(u32::MAX),
+        shape: Shape::Rect { w: u32::MAX, h: u32::MAX },
+    };
+    for value in [min, max] {
+        same_as_codec(&value, &CodecPrimitives::from(&value)).unwrap();
+        roundtrips(&value).unwrap();
+    }
+}
+
+#[test]
+fn nested_value_is_length_prefixed() {
+    let msg = Msg { head: vec![1, 2], body: "nested".into() };
+    let out = serde_scale::to_vec(&(Nested(msg.clone()), 9u8)).unwrap();
+    assert_eq!(out[0], 10 << 2);
+    assert_eq!(out[1..11], msg.encode()[..]);
+    assert_eq!(out[11], 9);
+    let (nested, tail) = serde_scale::from_slice::<(Nested<Msg>, u8)>(&out).unwrap();
+    assert_eq!((nested.0, tail), (msg, 9));
+}
+
+#[test]
+fn nested_value_must_use_all_its_bytes() {
+    let res = serde_scale::from_slice::<Nested<u8>>(&[2 << 2, 1, 2]);
+    assert!(matches!(res, Err(serde_scale::Error::TrailingBytes { remaining: 1 })));
+    let res = serde_scale::from_slice::<Nested<u16>>(&[1 << 2, 1]);
+    assert!(matches!(res, Err(serde_scale::Error::Other(_))));
+}
+
+#[test]
+fn serialization_is_deterministic() {
+    let value = (
+        mixed_expressions(),
+        linked_list(&[3, 1, 2]),
+        map_of_pairs(),
+        (0..40u16).map(|i| format!("{}", i * 37 % 41)).collect::<BTreeSet<_>>(),
+        [Some(true), None, Some(false)],
+        Compact(u64::MAX),
+    );
+    let out = serde_scale::to_vec(&value).unwrap();
+    assert_eq!(out, serde_scale::to_vec(&value).unwrap());
+    assert_eq!(out, serde_scale::to_vec_pooled(&value).unwrap());
+    assert_eq!(out, serde_scale::to_vec_with_capacity(&value, out.len()).unwrap());
+
+    // Each `HashMap` has its own random hasher, hence its own iteration order
+    let to_vec_sorted = |x: &HashMap<String, Vec<u8>>| {
+        let mut serializer = serde_scale::Serializer::new(Vec::new()).with_sorted_maps();
+        x.serialize(&mut serializer).unwrap();
+        serializer.into_inner()
+    };
+    let entries = (0..100u8).map(|i| (format!("key{}", i), vec![i; usize::from(i % 5)]));
+    let maps = (0..4).map(|_| entries.clone().collect::<HashMap<_, _>>()).collect::<Vec<_>>();
+    let out = to_vec_sorted(&maps[0]);
+    for map in &maps[1..] {
+        assert_eq!(to_vec_sorted(map), out);
+    }
+}
+
+#[test]
+fn slice_serializes_like_vec() {
+    let slice = &[1u32, 2, 3][..];
+    let out = serde_scale::to_vec(&slice).unwrap();
+    assert_eq!(out, serde_scale::to_vec(&slice.to_vec()).unwrap());
+    assert_eq!(out, slice.encode());
+}
+
+#[test]
+fn empty_slice_serializes_like_vec() {
+    let slice: &[u32] = &[];
+    let out = serde_scale::to_vec(&slice).unwrap();
+    assert_eq!(out, [0]);
+    assert_eq!(out, slice.encode());
+}
+
+#[test]
+fn pair_of_16_bit_integers_has_expected_bytes() {
+    let pair = Pair { a: 0x0102, b: -2 };
+    assert_eq!(serde_scale::to_vec(&pair).unwrap(), [0x02, 0x01, 0xfe, 0xff]);
+    assert_eq!(serde_scale::to_vec(&vec![pair]).unwrap(), [0x04, 0x02, 0x01, 0xfe, 0xff]);
+}
+
+#[test]
+fn supplementary_plane_chars_serialize_as_codepoints() {
+    assert_eq!(serde_scale::to_vec(&'\u{1d11e}').unwrap(), [0x1e, 0xd1, 0x01, 0x00]);
+    assert_eq!(serde_scale::to_vec(&'\u{1f600}').unwrap(), [0x00, 0xf6, 0x01, 0x00]);
+}
+
+#[test]
+fn arithmetic_wrappers_serialize_transparently() {
+    assert_eq!(
+        serde_scale::to_vec(&Wrapping(0x0102_0304_u32)).unwrap(),
+        serde_scale::to_vec(&0x0102_0304_u32).unwrap(),
+    );
+    assert_eq!(
+        serde_scale::to_vec(&Saturating(0x0102_0304_u64)).unwrap(),
+        serde_scale::to_vec(&0x0102_0304_u64).unwrap(),
+    );
+}
+
+#[test]
+fn empty_collections_next_to_non_empty_ones_have_expected_bytes() {
+    let msg = Msg { head: vec![], body: "ab".into() };
+    assert_eq!(serde_scale::to_vec(&msg).unwrap(), [0, 8, b'a', b'b']);
+    let msg = Msg { head: vec![9], body: String::new() };
+    assert_eq!(serde_scale::to_vec(&msg).unwrap(), [4, 9, 0]);
+}
+
+#[test]
+fn either_variants_have_indices_0_and_1() {
+    assert_eq!(serde_scale::to_vec(&Either::<u8, u16>::Left(7)).unwrap(), [0, 7]);
+    assert_eq!(serde_scale::to_vec(&Either::<u8, u16>::Right(7)).unwrap(), [1, 7, 0]);
+}
+
+#[test]
+fn option_discriminant_precedes_struct() {
+    let op = Some(Operator { name: "+".into(), priority: 2 });
+    assert_eq!(serde_scale::to_vec(&op).unwrap(), [1, 4, b'+', 2]);
+    assert_eq!(serde_scale::to_vec(&None::<Operator>).unwrap(), [0]);
+}
+
+#[test]
+fn reverse_serializes_transparently() {
+    let set = [1u8, 2, 3].iter().copied().map(Reverse).collect::<BTreeSet<_>>();
+    let out = serde_scale::to_vec(&set).unwrap();
+    assert_eq!(out, [12, 3, 2, 1]);
+    assert_eq!(out, serde_scale::to_vec(&vec![3u8, 2, 1]).unwrap());
+    assert_eq!(serde_scale::from_slice::<BTreeSet<Reverse<u8>>>(&out).unwrap(), set);
+}
+
+#[test]
+fn vec_lengths_at_compact_boundaries_match_codec() {
+    let cases: [(usize, &[u8]); 4] = [
+        (63, &[0xfc]),
+        (64, &[0x01, 0x01]),
+        (16383, &[0xfd, 0xff]),
+        (16384, &[0x02, 0x00, 0x01, 0x00]),
+    ];
+    for (len, prefix) in cases {
+        let v = vec![0xab_u8; len];
+        let out = serde_scale::to_vec(&v).unwrap();
+        assert_eq!(&out[..prefix.len()], prefix, "Length {}", len);
+        assert_eq!(out, v.encode(), "Length {}", len);
+        assert_eq!(serde_scale::from_slice::<Vec<u8>>(&out).unwrap(), v);
+        let s = "a".repeat(len);
+        let out = serde_scale::to_vec(&s).unwrap();
+        assert_eq!(out, s.encode(), "Length {}", len);
+        assert_eq!(serde_scale::from_slice::<String>(&out).unwrap(), s);
+    }
+}
+
+#[test]
+fn multibyte_strings_have_expected_bytes() {
+    let s = "h\u{e9}llo \u{1f600} \u{4e16}\u{754c}";
+    assert_eq!(s.len(), 18);
+    let out = serde_scale::to_vec(&s).unwrap();
+    assert_eq!(out[0], 18 << 2);
+    assert_eq!(&out[1..], s.as_bytes());
+    assert_eq!(out, s.encode());
+    let long = "\u{4e16}".repeat(30);
+    let out = serde_scale::to_vec(&long).unwrap();
+    assert_eq!(out[..2], [0x69, 0x01]);
+    assert_eq!(out, long.encode());
+    assert_eq!(serde_scale::from_slice::<String>(&out).unwrap(), long);
+}
+
+#[test]
+fn invalid_utf8_is_rejected() {
+    let truncated_emoji = [3 << 2, 0xf0, 0x9f, 0x98];
+    assert!(matches!(
+        serde_scale::from_slice::<String>(&truncated_emoji),
+        Err(serde_scale::Error::InvalidUnicode(_)),
+    ));
+    assert!(matches!(
+        serde_scale::from_slice::<&str>(&[2 << 2, b'a', 0xff]),
+        Err(serde_scale::Error::InvalidUnicode(e)) if e.valid_up_to() == 1,
+    ));
+}
+
+#[test]
+fn mixed_expressions_have_expected_bytes() {
+    let out = serde_scale::to_vec(&mixed_expressions()).unwrap();
+    assert_eq!(out[..8], [16, 0, 1, 0, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(out, mixed_expressions().encode());
+}
+
+fn mixed_expressions() -> Vec<Option<Expression>> {
+    let op = |name: &str, priority, lhs, rhs| {
+        Expression::Op(
+            Box::new(lhs),
+            Operator { name: name.into(), priority },
+            Box::new(rhs),
+        )
+    };
+    vec![
+        None,
+        Some(Expression::Const(-1)),
+        Some(op(
+            "*",
+            3,
+            op("+", 2, Expression::Const(1), Expression::Const(2)),
+            Expression::Const(i32::MAX),
+        )),
+        None,
+    ]
+}
+
+#[test]
+fn nested_maps_emit_one_length_per_level() {
+    let nested = (1..=3u8)
+        .map(|i| (i, (0..i).map(|j| (j, u32::from(i) << 8 | u32::from(j))).collect()))
+        .collect::<HashMap<u8, HashMap<u8, u32>>>();
+    let out = serde_scale::to_vec(&nested).unwrap();
+    assert_eq!(out.len(), 1 + 3 * 2 + 6 * 5);
+    let rebuilt = serde_scale::from_slice::<HashMap<u8, HashMap<u8, u32>>>(&out).unwrap();
+    assert_eq!(rebuilt, nested);
+    let sorted = nested
+        .iter()
+        .map(|(k, v)| (*k, v.iter().map(|(a, b)| (*a, *b)).collect::<BTreeMap<_, _>>()))
+        .collect::<BTreeMap<_, _>>();
+    let single_entries = sorted
+        .iter()
+        .filter(|(_, v)| v.len() == 1)
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(serde_scale::to_vec(&single_entries).unwrap(), [4, 1, 4, 0, 0, 1, 0, 0]);
+    assert_eq!(serde_scale::to_vec(&sorted).unwrap(), sorted.encode());
+    let mut serializer = serde_scale::Serializer::new(Vec::new()).with_sorted_maps();
+    nested.serialize(&mut serializer).unwrap();
+    assert_eq!(serializer.into_inner(), sorted.encode());
+}
+
+#[test]
+fn mixed_signs_have_expected_bytes() {
+    let value = MixedSigns { a: -2, b: 0x0102, c: i32::MIN, d: 0x0304_0506_0708_090a };
+    let out = serde_scale::to_vec(&value).unwrap();
+    assert_eq!(
+        out,
+        [0xfe, 0x02, 0x01, 0x00, 0x00, 0x00, 0x80, 0x0a, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03],
+    );
+    assert_eq!(out, value.encode());
+}
+
+#[test]
+fn compact_matches_codec_across_u64_range() {
+    let values = (0..64)
+        .flat_map(|shift| {
+            let p = 1u64 << shift;
+            [p - 1, p, p + 1, p | p >> 1, p.wrapping_mul(0x0101_0101_0101_0101) | p]
+        })
+        .chain([u64::MAX - 1, u64::MAX]);
+    for v in values {
+        let out = serde_scale::to_vec(&Compact(v)).unwrap();
+        assert_eq!(out, CodecCompact(v).encode(), "Value {:#x}", v);
+        assert_eq!(serde_scale::from_slice::<Compact<u64>>(&out).unwrap(), Compact(v));
+    }
+}
+
+#[test]
+fn compact_u128_matches_codec_across_range() {
+    let values = (0..128)
+        .flat_map(|shift| {
+            let p = 1u128 << shift;
+            [p - 1, p, p + 1]
+        })
+        .chain([u128::MAX]);
+    for v in values {
+        let out = serde_scale::to_vec(&Compact(v)).unwrap();
+        assert_eq!(out, CodecCompact(v).encode(), "Value {:#x}", v);
+        assert_eq!(serde_scale::from_slice::<Compact<u128>>(&out).unwrap(), Compact(v));
+    }
+}
+
+#[test]
+fn raw_bytes_match_codec_arrays() {
+    let hash = core::array::from_fn(|i| i as u8);
+    let parent = core::array::from_fn(|i| 0xff - i as u8);
+    let header = Header { number: 7, hash: RawBytes(hash), parent: RawBytes(parent) };
+    let mut codec_parent = [[0; 16]; 3];
+    for (dst, src) in codec_parent.iter_mut().zip(parent.chunks(16)) {
+        dst.copy_from_slice(src);
+    }
+    let codec_header = CodecHeader { number: 7, hash, parent: codec_parent };
+    same_as_codec(&header, &codec_header).unwrap();
+    roundtrips(&header).unwrap();
+    let out = serde_scale::to_vec(&RawBytes(hash)).unwrap();
+    assert_eq!(out, hash.encode());
+    let borrowed = serde_scale::from_slice::<RawBytes<32>>(&out).unwrap();
+    assert_eq!(borrowed, RawBytes(hash));
+}
+
+#[test]
+fn raw_bytes_fail_on_short_input() {
+    assert!(serde_scale::from_slice::<RawBytes<32>>(&[0; 31]).is_err());
+}
+
+#[test]
+fn wide_integers_serialize_like_codec() {
+    for v in [0, 1, 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10, u128::MAX] {
+        assert_eq!(serde_scale::to_vec(&v).unwrap(), v.encode());
+    }
+    for v in [i128::MIN, -1, 0, 1, i128::MAX] {
+        assert_eq!(serde_scale::to_vec(&v).unwrap(), v.encode());
+    }
+    assert_eq!(serde_scale::to_vec(&1u128).unwrap()[..2], [1, 0]);
+    assert_eq!(serde_scale::to_vec(&1u128).unwrap().len(), 16);
+}
+
+#[test]
+fn wide_integers_deserialize_from_codec_bytes() {
+    assert_eq!(serde_scale::from_slice::<u128>(&u128::MAX.encode()).unwrap(), u128::MAX);
+    assert_eq!(serde_scale::from_slice::<i128>(&i128::MIN.encode()).unwrap(), i128::MIN);
+    let wide = Wide { a: u128::MAX, b: i128::MIN, c: 3 };
+    assert_eq!(serde_scale::from_slice::<Wide>(&wide.encode()).unwrap(), wide);
+}
+
+#[test]
+fn options_of_bool_take_one_byte_in_composites() {
+    let v = vec![None, Some(true), Some(false)];
+    assert_eq!(serde_scale::to_vec(&v).unwrap(), [12, 0, 1, 2]);
+    assert_eq!(serde_scale::to_vec(&(true, Some(false))).unwrap(), [1, 2]);
+    assert_eq!(serde_scale::to_vec(&Ok::<bool, u8>(true)).unwrap(), [0, 1]);
+}
+
+/// Version written as text for human-readable formats and as a `u16` otherwise
+#[derive(Debug, PartialEq)]
+struct Version(u16);
+
+impl Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&format_args!("v{}", self.0))
+        } else {
+            serializer.serialize_u16(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.strip_prefix('v')
+                .and_then(|n| n.parse().ok())
+                .map(Version)
+                .ok_or_else(|| serde::de::Error::custom("Invalid version"))
+        } else {
+            u16::deserialize(deserializer).map(Version)
+        }
+    }
+}
+
+#[test]
+fn binary_representation_is_chosen() {
+    let out = serde_scale::to_vec(&Version(0x0102)).unwrap();
+    assert_eq!(out, 0x0102u16.encode());
+    assert_eq!(serde_scale::from_slice::<Version>(&out).unwrap(), Version(0x0102));
+    assert_eq!(serde_scale::to_vec(&Some(Version(3))).unwrap(), Some(3u16).encode());
+}
+
+fn map_of_pairs() -> BTreeMap<u8, u32> {
+    vec![(1, 10), (2, 0x0102_0304), (255, u32::MAX)].into_iter().collect()
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct Pair {
+    a: u16,
+    b: i16,
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct MixedSigns {
+    a: i8,
+    b: u16,
+    c: i32,
+    d: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct Wide {
+    a: u128,
+    b: i128,
+    c: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct CompactFields {
+    #[codec(compact)]
+    #[serde(with = "serde_scale::compact")]
+    a: u8,
+    #[codec(compact)]
+    #[serde(with = "serde_scale::compact")]
+    b: u16,
+    #[codec(compact)]
+    #[serde(with = "serde_scale::compact")]
+    c: u32,
+    #[codec(compact)]
+    #[serde(with = "serde_scale::compact")]
+    d: u64,
+    #[codec(compact)]
+    #[serde(with = "serde_scale::compact")]
+    e: u128,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct Header {
+    number: u32,
+    hash: RawBytes<32>,
+    parent: RawBytes<48>,
+}
+
+#[derive(Encode)]
+struct CodecHeader {
+    number: u32,
+    hash: [u8; 32],
+    parent: [[u8; 16]; 3],
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct Primitives {
+    flag: bool,
+    i8: i8,
+    i16: i16,
+    i32: i32,
+    i64: i64,
+    i128: i128,
+    u8: u8,
+    u16: u16,
+    u32: u32,
+    u64: u64,
+    u128: u128,
+    ch: char,
+    text: String,
+    blob: Vec<u8>,
+    maybe: Option<u32>,
+    shape: Shape,
+}
+
+/// Twin of `Primitives` encoded by the codec, which encodes `char` as `u32`
+#[derive(Encode)]
+struct CodecPrimitives {
+    flag: bool,
+    i8: i8,
+    i16: i16,
+    i32: i32,
+    i64: i64,
+    i128: i128,
+    u8: u8,
+    u16: u16,
+    u32: u32,
+    u64: u64,
+    u128: u128,
+    ch: u32,
+    text: String,
+    blob: Vec<u8>,
+    maybe: Option<u32>,
+    shape: Shape,
+}
+
+impl From<&Primitives> for CodecPrimitives {
+    fn from(p: &Primitives) -> Self {
+        CodecPrimitives {
+            flag: p.flag,
+            i8: p.i8,
+            i16: p.i16,
+            i32: p.i32,
+            i64: p.i64,
+            i128: p.i128,
+            u8: p.u8,
+            u16: p.u16,
+            u32: p.u32,
+            u64: p.u64,
+            u128: p.u128,
+            ch: p.ch.into(),
+            text: p.text.clone(),
+            blob: p.blob.clone(),
+            maybe: p.maybe,
+            shape: p.shape.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct Node {
+    value: u32,
+    next: Option<Box<Node>>,
+}
+
+fn linked_list(values: &[u32]) -> Node {
+    let (&last, rest) = values.split_last().unwrap();
+    rest.iter().rev().fold(Node { value: last, next: None }, |next, &value| {
+        Node { value, next: Some(Box::new(next)) }
+    })
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+enum Shape {
+    Circle { r: u32 },
+    Rect { w: u32, h: u32 },
+}
+
+#[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
+struct Msg {
+    head: Vec<u8>,
+    body: String,
+}
+
+#[derive(Clone, Debug, Encode, PartialEq)]
+enum EitherLike {
+    Left(u8),
+    Right(String),
+}
+
 #[derive(Clone, Debug, Deserialize, Encode, PartialEq, Serialize)]
 struct Operator {
     name: String,
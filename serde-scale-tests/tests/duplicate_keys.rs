@@ -0,0 +1,75 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_scale::{Deserializer, EndOfInput, Error};
+use std::collections::{BTreeMap, HashMap};
+
+fn from_slice_unique<T: DeserializeOwned>(v: &[u8]) -> Result<T, Error<EndOfInput>> {
+    T::deserialize(&mut Deserializer::new(v).with_unique_map_keys())
+}
+
+fn entries<K: Serialize, V: Serialize>(pairs: &[(K, V)]) -> Vec<u8> {
+    serde_scale::to_vec(&pairs).unwrap()
+}
+
+#[test]
+fn unique_keys_are_accepted() {
+    let bytes = entries(&[(1u8, "a"), (2, "b"), (3, "c")]);
+    let map = from_slice_unique::<BTreeMap<u8, String>>(&bytes).unwrap();
+    assert_eq!(map.len(), 3);
+}
+
+#[test]
+fn duplicate_keys_are_rejected() {
+    let bytes = entries(&[(1u8, "a"), (2, "b"), (1, "smuggled")]);
+    assert!(matches!(
+        from_slice_unique::<HashMap<u8, String>>(&bytes),
+        Err(Error::DuplicateMapKey { index: 2 }),
+    ));
+}
+
+#[test]
+fn duplicate_keys_are_deduplicated_by_default() {
+    let bytes = entries(&[(1u8, "a"), (1, "b")]);
+    let map = serde_scale::from_slice::<BTreeMap<u8, String>>(&bytes).unwrap();
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn compound_keys_are_compared_by_bytes() {
+    let bytes = entries(&[(("x", 1u16), 0u8), (("x", 2), 0), (("y", 1), 0)]);
+    assert!(from_slice_unique::<BTreeMap<(String, u16), u8>>(&bytes).is_ok());
+    let bytes = entries(&[(("x", 1u16), 0u8), (("x", 1), 1)]);
+    assert!(matches!(
+        from_slice_unique::<BTreeMap<(String, u16), u8>>(&bytes),
+        Err(Error::DuplicateMapKey { index: 1 }),
+    ));
+}
+
+#[test]
+fn nested_maps_are_checked() {
+    let inner_ok = entries(&[(1u8, 1u8), (2, 2)]);
+    let inner_dup = entries(&[(1u8, 1u8), (1, 2)]);
+    let mut bytes = vec![4, 0];
+    bytes.extend(&inner_ok);
+    assert!(from_slice_unique::<BTreeMap<u8, BTreeMap<u8, u8>>>(&bytes).is_ok());
+    let mut bytes = vec![4, 0];
+    bytes.extend(&inner_dup);
+    assert!(matches!(
+        from_slice_unique::<BTreeMap<u8, BTreeMap<u8, u8>>>(&bytes),
+        Err(Error::DuplicateMapKey { index: 1 }),
+    ));
+}
+
+#[test]
+fn map_keys_can_be_maps() {
+    let key_a = BTreeMap::from([(1u8, 2u8)]);
+    let key_b = BTreeMap::from([(1u8, 3u8)]);
+    let bytes = entries(&[(key_a.clone(), 0u8), (key_b, 1)]);
+    assert!(from_slice_unique::<BTreeMap<BTreeMap<u8, u8>, u8>>(&bytes).is_ok());
+    let bytes = entries(&[(key_a.clone(), 0u8), (key_a, 1)]);
+    assert!(matches!(
+        from_slice_unique::<BTreeMap<BTreeMap<u8, u8>, u8>>(&bytes),
+        Err(Error::DuplicateMapKey { index: 1 }),
+    ));
+}
@@ -0,0 +1,103 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use serde_scale::{Deserializer, EndOfInput, Error, PathSegment};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Inner {
+    id: u8,
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum Shape {
+    Circle { r: u8 },
+    Tagged(u8, bool),
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Outer {
+    name: String,
+    items: Vec<Inner>,
+    shape: Shape,
+    flags: BTreeMap<u8, bool>,
+}
+
+fn sample() -> Outer {
+    Outer {
+        name: "outer".into(),
+        items: vec![
+            Inner { id: 0, enabled: true },
+            Inner { id: 1, enabled: false },
+            Inner { id: 2, enabled: true },
+        ],
+        shape: Shape::Tagged(3, true),
+        flags: vec![(1, true), (2, false)].into_iter().collect(),
+    }
+}
+
+fn deserialize_with_path(bytes: &[u8]) -> Error<EndOfInput> {
+    Outer::deserialize(&mut Deserializer::new(bytes).with_error_path()).unwrap_err()
+}
+
+/// Returns the serialized sample with the last byte of the serialized `needle` replaced by 7
+fn corrupt(needle: &[u8]) -> Vec<u8> {
+    let mut bytes = serde_scale::to_vec(&sample()).unwrap();
+    let pos = bytes.windows(needle.len()).position(|w| w == needle).unwrap() + needle.len() - 1;
+    bytes[pos] = 7;
+    bytes
+}
+
+#[test]
+fn path_leads_to_field_inside_vector() {
+    let e = deserialize_with_path(&corrupt(&[2, 1]));
+    match &e {
+        Error::WithPath { path, offset, error } => {
+            assert_eq!(*offset, 12);
+            assert_eq!(
+                path[..],
+                [PathSegment::Field("items"), PathSegment::Index(2), PathSegment::Field("enabled")],
+            );
+            assert!(matches!(**error, Error::ExpectedBoolean { found: 7 }));
+        }
+        e => panic!("Unexpected error: {:?}", e),
+    }
+    assert_eq!(e.to_string(), "Expected boolean (0 or 1), found 7 at .items[2].enabled");
+    assert_eq!(
+        e.report(),
+        "error: Expected boolean (0 or 1), found 7\n  found: 7\n  offset: 12\n  \
+            path: .items[2].enabled",
+    );
+}
+
+#[test]
+fn path_includes_enum_variants_and_map_entries() {
+    let e = deserialize_with_path(&corrupt(&[1, 3, 1]));
+    assert_eq!(e.to_string(), "Expected boolean (0 or 1), found 7 at .shape::Tagged[1]");
+    let e = deserialize_with_path(&corrupt(&[2, 0]));
+    assert_eq!(e.to_string(), "Expected boolean (0 or 1), found 7 at .flags[1]");
+}
+
+#[test]
+fn truncated_input_reports_path() {
+    let bytes = serde_scale::to_vec(&sample()).unwrap();
+    let e = deserialize_with_path(&bytes[..8]);
+    assert_eq!(e.to_string(), "I/O error: EndOfInput at .items[0].enabled");
+}
+
+#[test]
+fn errors_have_no_path_by_default() {
+    let res = serde_scale::from_slice::<Outer>(&corrupt(&[2, 1]));
+    assert!(matches!(res, Err(Error::ExpectedBoolean { found: 7 })));
+}
+
+#[test]
+fn path_does_not_affect_successful_deserialization() {
+    let bytes = serde_scale::to_vec(&sample()).unwrap();
+    let mut deserializer = Deserializer::new(&bytes[..]).with_error_path();
+    assert_eq!(Outer::deserialize(&mut deserializer).unwrap(), sample());
+    let res = Outer::deserialize(&mut deserializer);
+    let expected = PathSegment::Field("name");
+    assert!(matches!(res, Err(Error::WithPath { ref path, .. }) if path[..] == [expected]));
+}
@@ -0,0 +1,199 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::Deserialize;
+use serde_scale::{
+    Compact, Deserializer, Error, FrameError, FrameReader, FramedReader, FramingWriter,
+};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, Read},
+    rc::Rc,
+};
+
+/// Non-blocking stream fed by the test
+#[derive(Clone, Default)]
+struct Pipe {
+    data: Rc<RefCell<VecDeque<u8>>>,
+    closed: Rc<RefCell<bool>>,
+}
+
+impl Pipe {
+    fn feed(&self, bytes: &[u8]) {
+        self.data.borrow_mut().extend(bytes);
+    }
+
+    fn close(&self) {
+        *self.closed.borrow_mut() = true;
+    }
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut data = self.data.borrow_mut();
+        if data.is_empty() {
+            return if *self.closed.borrow() {
+                Ok(0)
+            } else {
+                Err(io::ErrorKind::WouldBlock.into())
+            };
+        }
+        let n = buf.len().min(data.len());
+        for (dst, src) in buf.iter_mut().zip(data.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    serde_scale::to_vec(&payload.to_vec()).unwrap()
+}
+
+#[test]
+fn partial_frame_is_buffered_until_complete() {
+    let pipe = Pipe::default();
+    let mut reader = FrameReader::new(pipe.clone());
+    let bytes = frame(b"hello");
+    pipe.feed(&bytes[..3]);
+    assert!(matches!(
+        reader.next_frame(),
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock,
+    ));
+    pipe.feed(&bytes[3..]);
+    assert_eq!(reader.next_frame().unwrap().unwrap(), b"hello");
+}
+
+#[test]
+fn several_frames_in_one_read_are_split() {
+    let mut bytes = frame(b"one");
+    bytes.extend(frame(b""));
+    bytes.extend(frame(&[b'x'; 100]));
+    let mut reader = FrameReader::new(&bytes[..]);
+    assert_eq!(reader.next_frame().unwrap().unwrap(), b"one");
+    assert_eq!(reader.next_frame().unwrap().unwrap(), b"");
+    assert_eq!(reader.next_frame().unwrap().unwrap(), [b'x'; 100]);
+    assert!(reader.next_frame().unwrap().is_none());
+}
+
+#[test]
+fn frame_can_be_deserialized() {
+    let bytes = frame(&serde_scale::to_vec(&(7u8, String::from("foo"))).unwrap());
+    let mut reader = FrameReader::new(&bytes[..]);
+    let frame = reader.next_frame().unwrap().unwrap();
+    let value = serde_scale::from_slice::<(u8, String)>(&frame).unwrap();
+    assert_eq!(value, (7, String::from("foo")));
+}
+
+#[test]
+fn stream_ending_mid_frame_fails() {
+    let pipe = Pipe::default();
+    let mut reader = FrameReader::new(pipe.clone());
+    pipe.feed(&frame(b"hello")[..4]);
+    pipe.close();
+    assert!(matches!(
+        reader.next_frame(),
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof,
+    ));
+}
+
+#[test]
+fn written_frames_are_read_back() {
+    let payloads = [b"first".to_vec(), vec![], vec![0xab; 300]];
+    let mut writer = FramingWriter::new(Vec::new());
+    for payload in &payloads {
+        writer.write_frame(payload).unwrap();
+    }
+    let bytes = writer.into_inner();
+    assert_eq!(bytes[..6], frame(b"first")[..]);
+    let mut reader = FrameReader::new(&bytes[..]);
+    for payload in &payloads {
+        assert_eq!(reader.next_frame().unwrap().as_ref(), Some(payload));
+    }
+    assert_eq!(reader.next_frame().unwrap(), None);
+}
+
+fn framed(input: &[u8], len: usize) -> Deserializer<FramedReader<&[u8]>> {
+    Deserializer::new(FramedReader::new(input, len))
+}
+
+#[test]
+fn exact_frame_is_fully_consumed() {
+    let input = [1, 0, 2, 0, 9];
+    let mut deserializer = Deserializer::new(FramedReader::new(&input[..], 4).exact());
+    let value = <(u16, u16)>::deserialize(&mut deserializer).unwrap();
+    assert_eq!(value, (1, 2));
+    assert_eq!(deserializer.into_inner().finish().unwrap(), [9]);
+}
+
+#[test]
+fn under_consuming_exact_frame_fails() {
+    let input = [1, 0, 2, 0];
+    let mut deserializer = Deserializer::new(FramedReader::new(&input[..], 4).exact());
+    assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 1);
+    let res = deserializer.into_inner().finish();
+    assert!(matches!(res, Err(Error::TrailingBytes { remaining: 2 })));
+}
+
+#[test]
+fn under_consuming_frame_skips_leftover() {
+    let input = [1, 0, 2, 0, 9];
+    let mut deserializer = framed(&input, 4);
+    assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 1);
+    let reader = deserializer.into_inner();
+    assert_eq!(reader.into_remaining(), 2);
+    let mut deserializer = framed(&input, 4);
+    assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 1);
+    assert_eq!(deserializer.into_inner().finish().unwrap(), [9]);
+}
+
+#[test]
+fn over_reading_frame_fails() {
+    let input = [1, 0, 2, 0];
+    let mut deserializer = framed(&input, 3);
+    let res = <(u16, u16)>::deserialize(&mut deserializer);
+    assert!(matches!(res, Err(Error::Io(FrameError::EndOfFrame))));
+}
+
+/// Deserializes a `T` from a frame whose length is given by a compact prefix
+fn read_prefixed<'a, T: Deserialize<'a>>(
+    input: &'a [u8],
+) -> Result<(T, &'a [u8]), Error<FrameError<serde_scale::EndOfInput>>> {
+    let mut deserializer = Deserializer::new(input);
+    let Compact(len) = Compact::<u32>::deserialize(&mut deserializer).unwrap();
+    let mut framed = deserializer.with_exact_frame(len as usize);
+    let value = T::deserialize(&mut framed)?;
+    let rest = framed.into_inner().finish()?;
+    Ok((value, rest))
+}
+
+#[test]
+fn matching_inner_length_is_accepted() {
+    let input = [3 << 2, 1, 2, 0, 7];
+    let (value, rest) = read_prefixed::<(u8, u16)>(&input).unwrap();
+    assert_eq!(value, (1, 2));
+    assert_eq!(rest, [7]);
+}
+
+#[test]
+fn inner_length_too_long_is_rejected() {
+    let input = [4 << 2, 1, 2, 0, 7];
+    let res = read_prefixed::<(u8, u16)>(&input);
+    assert!(matches!(res, Err(Error::TrailingBytes { remaining: 1 })));
+}
+
+#[test]
+fn inner_length_too_short_is_rejected() {
+    let input = [2 << 2, 1, 2, 0, 7];
+    let res = read_prefixed::<(u8, u16)>(&input);
+    assert!(matches!(res, Err(Error::Io(FrameError::EndOfFrame))));
+}
+
+#[test]
+fn exact_frame_keeps_deserializer_options() {
+    let input = [3, 0, 0, 0];
+    let deserializer = Deserializer::new(&input[..]).with_leb128_lengths();
+    let mut framed = deserializer.with_exact_frame(4);
+    assert_eq!(<Vec<u8>>::deserialize(&mut framed).unwrap(), [0, 0, 0]);
+    assert!(framed.into_inner().finish().is_ok());
+}
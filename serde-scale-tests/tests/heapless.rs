@@ -0,0 +1,30 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::Serialize;
+use serde_scale::{BufferOverflow, Error, Serializer};
+
+#[derive(Serialize)]
+struct Reading {
+    sensor: u8,
+    value: i32,
+    label: &'static str,
+}
+
+const READING: Reading = Reading { sensor: 2, value: -5, label: "temp" };
+
+#[test]
+fn value_fills_buffer_exactly() {
+    let mut serializer = Serializer::new(heapless::Vec::<u8, 10>::new());
+    READING.serialize(&mut serializer).unwrap();
+    let out = serializer.into_inner();
+    assert!(out.is_full());
+    assert_eq!(out[..], serde_scale::to_vec(&READING).unwrap()[..]);
+}
+
+#[test]
+fn value_overflowing_buffer_fails() {
+    let mut serializer = Serializer::new(heapless::Vec::<u8, 9>::new());
+    let res = READING.serialize(&mut serializer);
+    assert!(matches!(res, Err(Error::Io(BufferOverflow))));
+    assert_eq!(serializer.into_inner()[..], [2, 0xfb, 0xff, 0xff, 0xff, 16]);
+}
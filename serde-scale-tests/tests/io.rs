@@ -0,0 +1,144 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use serde_scale::{Deserializer, Error, IoReader, IoWriter, Serializer};
+use std::io::{self, Cursor};
+
+/// Writer accepting at most `max` bytes per call
+struct Trickle {
+    max: usize,
+    out: Vec<u8>,
+}
+
+impl io::Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.max);
+        self.out.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn partial_writes_are_completed() {
+    let value = ("a longer string than the writer accepts at once", vec![1u32, 2, 3]);
+    for max in [1, 3, 7] {
+        let mut serializer = Serializer::new(IoWriter(Trickle { max, out: Vec::new() }));
+        value.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.into_inner().into_inner().out, serde_scale::to_vec(&value).unwrap());
+    }
+}
+
+#[test]
+fn writer_making_no_progress_fails() {
+    let mut serializer = Serializer::new(IoWriter(Trickle { max: 0, out: Vec::new() }));
+    let res = "abc".serialize(&mut serializer);
+    assert!(matches!(res, Err(Error::Io(e)) if e.kind() == io::ErrorKind::WriteZero));
+}
+
+#[test]
+fn io_writer_writes_to_slices() {
+    let mut buf = [0u8; 3];
+    let mut serializer = Serializer::new(IoWriter(&mut buf[..]));
+    0x0102_u16.serialize(&mut serializer).unwrap();
+    assert!(0x0304_u16.serialize(&mut serializer).is_err());
+    assert_eq!(buf[..2], [0x02, 0x01]);
+}
+
+#[test]
+fn values_stream_into_file() {
+    let path = std::env::temp_dir().join(format!("serde-scale-io-{}", std::process::id()));
+    let value = (vec![1u16, 2, 3], "file");
+    {
+        let mut file = io::BufWriter::new(std::fs::File::create(&path).unwrap());
+        serde_scale::to_writer(IoWriter(&mut file), &value).unwrap();
+        serde_scale::to_writer(IoWriter(&mut file), &7u8).unwrap();
+        io::Write::flush(&mut file).unwrap();
+    }
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut expected = serde_scale::to_vec(&value).unwrap();
+    expected.push(7);
+    assert_eq!(bytes, expected);
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Entry {
+    key: String,
+    values: Vec<u64>,
+    flag: Option<bool>,
+}
+
+#[test]
+fn values_roundtrip_through_reader() {
+    let entries = vec![
+        Entry { key: "a".into(), values: vec![1, 2], flag: Some(true) },
+        Entry { key: "b".repeat(100), values: vec![], flag: None },
+    ];
+    let mut cursor = Cursor::new(serde_scale::to_vec(&entries).unwrap());
+    cursor.get_mut().extend([9, 9]);
+    let read = serde_scale::from_reader::<_, Vec<Entry>>(&mut cursor).unwrap();
+    assert_eq!(read, entries);
+    assert_eq!(cursor.position() as usize, cursor.get_ref().len() - 2);
+}
+
+#[test]
+fn reader_ending_mid_value_fails() {
+    let mut bytes = serde_scale::to_vec(&("truncated", 5u32)).unwrap();
+    bytes.pop();
+    let res = serde_scale::from_reader::<_, (String, u32)>(Cursor::new(&bytes));
+    assert!(matches!(res, Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof));
+    let res = serde_scale::from_reader::<_, String>(Cursor::new(&bytes[..5]));
+    assert!(matches!(res, Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn huge_length_does_not_allocate_upfront() {
+    let res = serde_scale::from_reader::<_, Vec<u8>>(Cursor::new([0x03, 0xff, 0xff, 0xff, 0xff]));
+    assert!(matches!(res, Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof));
+}
+
+/// Reader returning at most `max` bytes per call
+struct Dribble<'a> {
+    max: usize,
+    data: &'a [u8],
+}
+
+impl io::Read for Dribble<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.max).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn partial_reads_are_completed() {
+    let value = ("a longer string than the reader returns at once".to_owned(), vec![1u32, 2, 3]);
+    let bytes = serde_scale::to_vec(&value).unwrap();
+    for max in [1, 3, 7] {
+        let mut deserializer = Deserializer::new(IoReader::new(Dribble { max, data: &bytes }));
+        assert_eq!(<(String, Vec<u32>)>::deserialize(&mut deserializer).unwrap(), value);
+        assert!(deserializer.into_inner().into_inner().data.is_empty());
+    }
+}
+
+#[test]
+fn io_reader_cannot_lend_borrowed_values() {
+    let bytes = serde_scale::to_vec(&"borrowed").unwrap();
+    let mut deserializer = Deserializer::new(IoReader::new(&bytes[..]));
+    assert!(<&str>::deserialize(&mut deserializer).is_err());
+}
+
+#[test]
+fn stream_remaining_length_is_unknown() {
+    let bytes = serde_scale::to_vec(&1u8).unwrap();
+    let mut deserializer = Deserializer::new(IoReader::new(&bytes[..]));
+    assert_eq!(u8::deserialize(&mut deserializer).unwrap(), 1);
+    assert_eq!(deserializer.remaining(), None);
+    assert!(!deserializer.is_empty());
+}
@@ -0,0 +1,37 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use serde_scale::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Peer {
+    #[serde(with = "serde_scale::ip_addr")]
+    addr: IpAddr,
+    port: u16,
+}
+
+#[test]
+fn ipv4_serializes_as_0_then_4_bytes() {
+    let peer = Peer { addr: Ipv4Addr::new(10, 0, 0, 2).into(), port: 0x0102 };
+    let bytes = serde_scale::to_vec(&peer).unwrap();
+    assert_eq!(bytes, [0, 10, 0, 0, 2, 0x02, 0x01]);
+    assert_eq!(serde_scale::from_slice::<Peer>(&bytes).unwrap(), peer);
+}
+
+#[test]
+fn ipv6_serializes_as_1_then_16_bytes() {
+    let peer = Peer { addr: Ipv6Addr::LOCALHOST.into(), port: 80 };
+    let bytes = serde_scale::to_vec(&peer).unwrap();
+    let mut expected = vec![1];
+    expected.extend(Ipv6Addr::LOCALHOST.octets());
+    expected.extend(80u16.to_le_bytes());
+    assert_eq!(bytes, expected);
+    assert_eq!(serde_scale::from_slice::<Peer>(&bytes).unwrap(), peer);
+}
+
+#[test]
+fn unknown_discriminant_is_rejected() {
+    let res = serde_scale::from_slice::<Peer>(&[2, 1, 2, 3, 4, 0, 0]);
+    assert!(matches!(res, Err(Error::UnknownVariant { found_discriminant: 2, .. })));
+}
@@ -0,0 +1,73 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use serde_scale::ScaleLayout;
+
+#[derive(Deserialize, ScaleLayout, Serialize)]
+#[scale_layout(pinned("id", "name", "flags"))]
+struct User {
+    id: u32,
+    name: String,
+    flags: u8,
+}
+
+#[derive(ScaleLayout, Serialize)]
+struct Point(i32, i32);
+
+#[derive(ScaleLayout, Serialize)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(ScaleLayout, Serialize)]
+struct Reordered {
+    b: u8,
+    a: u8,
+}
+
+#[derive(ScaleLayout, Serialize)]
+#[serde(deny_unknown_fields, rename = "Account")]
+#[scale_layout(pinned("id", "display_name", "balance"))]
+struct Renamed {
+    id: u32,
+    #[serde(skip)]
+    cache: Option<String>,
+    #[serde(rename = "display_name")]
+    name: String,
+    #[serde(skip_serializing, default)]
+    legacy: u8,
+    #[serde(rename(serialize = "balance", deserialize = "amount"), default)]
+    value: u64,
+}
+
+#[test]
+fn fields_are_listed_in_declaration_order() {
+    assert_eq!(User::FIELDS, ["id", "name", "flags"]);
+    assert_eq!(Reordered::FIELDS, ["b", "a"]);
+}
+
+#[test]
+fn tuple_fields_are_named_after_their_index() {
+    assert_eq!(Point::FIELDS, ["0", "1"]);
+}
+
+#[test]
+fn generic_structures_are_supported() {
+    assert_eq!(<Wrapper<String>>::FIELDS, ["inner"]);
+}
+
+#[test]
+fn reordering_is_detected_at_runtime() {
+    const EXPECTED: &[&str] = &["a", "b"];
+    assert_ne!(Reordered::FIELDS, EXPECTED);
+}
+
+#[test]
+fn skipped_fields_are_omitted_and_renamed_fields_use_their_serde_name() {
+    assert_eq!(Renamed::FIELDS, ["id", "display_name", "balance"]);
+    let value = Renamed { id: 1, cache: None, name: "a".into(), legacy: 9, value: 2 };
+    let bytes = serde_scale::to_vec(&value).unwrap();
+    assert_eq!(bytes, serde_scale::to_vec(&(1u32, "a", 2u64)).unwrap());
+    let Renamed { cache, legacy, .. } = value;
+    assert_eq!((cache, legacy), (None, 9));
+}
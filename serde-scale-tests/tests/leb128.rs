@@ -0,0 +1,81 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_scale::{Compact, Deserializer, EndOfInput, Error, Nested, Serializer};
+use std::{collections::BTreeMap, fmt::Debug};
+
+fn to_vec_leb128<T: Serialize>(x: &T) -> Vec<u8> {
+    let mut serializer = Serializer::new(Vec::new()).with_leb128_lengths();
+    x.serialize(&mut serializer).unwrap();
+    serializer.into_inner()
+}
+
+fn from_slice_leb128<T: DeserializeOwned>(v: &[u8]) -> Result<T, Error<EndOfInput>> {
+    T::deserialize(&mut Deserializer::new(v).with_leb128_lengths())
+}
+
+fn roundtrip<T>(x: T)
+where
+    T: Debug + DeserializeOwned + PartialEq + Serialize,
+{
+    assert_eq!(from_slice_leb128::<T>(&to_vec_leb128(&x)).unwrap(), x);
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Record {
+    id: u32,
+    name: String,
+    tags: Vec<u16>,
+    counter: Compact<u32>,
+}
+
+#[test]
+fn short_lengths_take_one_byte() {
+    assert_eq!(to_vec_leb128(&vec![7u8; 3]), [3, 7, 7, 7]);
+    assert_eq!(to_vec_leb128(&vec![0u8; 127])[..1], [0x7f]);
+}
+
+#[test]
+fn long_lengths_use_continuation_bits() {
+    assert_eq!(to_vec_leb128(&vec![0u8; 128])[..2], [0x80, 0x01]);
+    assert_eq!(to_vec_leb128(&vec![0u8; 300])[..2], [0xac, 0x02]);
+}
+
+#[test]
+fn fixed_integers_and_compact_values_are_unchanged() {
+    let record = Record { id: 1, name: "a".into(), tags: vec![], counter: Compact(64) };
+    assert_eq!(to_vec_leb128(&record), [1, 0, 0, 0, 1, b'a', 0, 0x01, 0x01]);
+}
+
+#[test]
+fn values_roundtrip() {
+    roundtrip(vec![0u8; 300]);
+    roundtrip("x".repeat(20_000));
+    roundtrip((0..200u16).map(|i| (i, i.to_string())).collect::<BTreeMap<_, _>>());
+    roundtrip(Record { id: 7, name: "name".into(), tags: vec![1, 2, 3], counter: Compact(1 << 20) });
+}
+
+#[test]
+fn overlong_length_is_rejected() {
+    let mut bytes = vec![0xff; 10];
+    bytes.push(0x01);
+    assert!(matches!(
+        from_slice_leb128::<Vec<u8>>(&bytes),
+        Err(Error::CollectionTooLargeToDeserialize),
+    ));
+}
+
+#[test]
+fn scale_deserializer_does_not_read_leb128_lengths() {
+    let bytes = to_vec_leb128(&vec![0u8; 128]);
+    assert_ne!(serde_scale::from_slice::<Vec<u8>>(&bytes).ok(), Some(vec![0u8; 128]));
+}
+
+#[test]
+fn nested_values_use_leb128_lengths() {
+    let value = Nested(vec![7u8; 200]);
+    let bytes = to_vec_leb128(&value);
+    assert_eq!(bytes[..4], [0xca, 0x01, 0xc8, 0x01]);
+    assert_eq!(bytes.len(), 204);
+    roundtrip(value);
+}
@@ -0,0 +1,186 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::Deserialize;
+use serde_scale::{DeLimits, Deserializer, EndOfInput, Error};
+use std::collections::BTreeMap;
+
+fn from_slice_with_limits<'a, T>(v: &'a [u8], limits: DeLimits) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(&mut Deserializer::new(v).with_limits(limits))
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Nested {
+    Leaf,
+    Node(Box<Nested>),
+}
+
+fn nested_bytes(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![1; depth];
+    bytes.push(0);
+    bytes
+}
+
+#[test]
+fn no_limits_by_default() {
+    let bytes = serde_scale::to_vec(&vec![0u8; 100]).unwrap();
+    let v = from_slice_with_limits::<Vec<u8>>(&bytes, DeLimits::default()).unwrap();
+    assert_eq!(v.len(), 100);
+}
+
+#[test]
+fn seq_limit_is_enforced() {
+    let limits = DeLimits { max_seq: Some(2), ..DeLimits::default() };
+    let ok = serde_scale::to_vec(&vec![1u32, 2]).unwrap();
+    assert_eq!(from_slice_with_limits::<Vec<u32>>(&ok, limits).unwrap(), [1, 2]);
+    let too_long = serde_scale::to_vec(&vec![1u32, 2, 3]).unwrap();
+    let res = from_slice_with_limits::<Vec<u32>>(&too_long, limits);
+    assert!(matches!(res, Err(Error::SequenceLengthExceeded { len: 3, limit: 2 })));
+}
+
+#[test]
+fn map_limit_is_enforced() {
+    let limits = DeLimits { max_map: Some(1), ..DeLimits::default() };
+    let bytes = serde_scale::to_vec(&vec![(1u8, 2u8), (3, 4)]).unwrap();
+    let res = from_slice_with_limits::<BTreeMap<u8, u8>>(&bytes, limits);
+    assert!(matches!(res, Err(Error::MapLengthExceeded { len: 2, limit: 1 })));
+    let res = from_slice_with_limits::<Vec<(u8, u8)>>(&bytes, limits);
+    assert_eq!(res.unwrap(), [(1, 2), (3, 4)]);
+}
+
+#[test]
+fn bytes_limit_is_enforced() {
+    let limits = DeLimits { max_bytes: Some(3), ..DeLimits::default() };
+    let bytes = serde_scale::to_vec(&"abcd").unwrap();
+    let res = from_slice_with_limits::<String>(&bytes, limits);
+    assert!(matches!(res, Err(Error::BytesLengthExceeded { len: 4, limit: 3 })));
+    let bytes = serde_scale::to_vec(&"abc").unwrap();
+    assert_eq!(from_slice_with_limits::<String>(&bytes, limits).unwrap(), "abc");
+}
+
+#[test]
+fn depth_limit_is_enforced() {
+    let limits = DeLimits { max_depth: Some(4), ..DeLimits::default() };
+    let res = from_slice_with_limits::<Nested>(&nested_bytes(3), limits);
+    assert!(res.is_ok());
+    let res = from_slice_with_limits::<Nested>(&nested_bytes(4), limits);
+    assert!(matches!(res, Err(Error::RecursionLimitExceeded { limit: 4 })));
+}
+
+#[test]
+fn limits_apply_inside_nested_values() {
+    let limits = DeLimits { max_seq: Some(2), ..DeLimits::default() };
+    let bytes = serde_scale::to_vec(&serde_scale::Nested(vec![1u32, 2, 3])).unwrap();
+    let res = from_slice_with_limits::<serde_scale::Nested<Vec<u32>>>(&bytes, limits);
+    assert!(matches!(res, Err(Error::SequenceLengthExceeded { len: 3, limit: 2 })));
+    let limits = DeLimits { max_depth: Some(4), ..DeLimits::default() };
+    let mut bytes = vec![5 << 2];
+    bytes.extend(nested_bytes(4));
+    let res = from_slice_with_limits::<serde_scale::Nested<Nested>>(&bytes, limits);
+    assert!(matches!(res, Err(Error::RecursionLimitExceeded { limit: 4 })));
+}
+
+#[test]
+fn length_validator_rejects_second_large_length() {
+    let bytes = serde_scale::to_vec(&(vec![0u8; 100], vec![0u8; 100])).unwrap();
+    let mut large_seen = 0;
+    let mut deserializer = Deserializer::new(&bytes[..]).with_length_validator(move |len| {
+        if len < 64 {
+            return Ok(());
+        }
+        large_seen += 1;
+        if large_seen > 1 {
+            Err(format!("Length {} rejected", len).into())
+        } else {
+            Ok(())
+        }
+    });
+    let res = <(Vec<u8>, Vec<u8>)>::deserialize(&mut deserializer);
+    match res {
+        Err(Error::Other(e)) => assert_eq!(e.as_str(), "Length 100 rejected"),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn length_validator_sees_string_and_map_lengths() {
+    let value = ("abc".to_owned(), BTreeMap::from([(1u8, 2u8), (3, 4)]));
+    let bytes = serde_scale::to_vec(&value).unwrap();
+    let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log = seen.clone();
+    let mut deserializer = Deserializer::new(&bytes[..]).with_length_validator(move |len| {
+        log.borrow_mut().push(len);
+        Ok(())
+    });
+    let rebuilt = <(String, BTreeMap<u8, u8>)>::deserialize(&mut deserializer).unwrap();
+    assert_eq!(rebuilt, value);
+    assert_eq!(*seen.borrow(), [3, 2]);
+}
+
+#[test]
+fn max_len_applies_to_all_collections() {
+    let seq = serde_scale::to_vec(&vec![1u32, 2, 3]).unwrap();
+    let res = Vec::<u32>::deserialize(&mut Deserializer::new(&seq[..]).with_max_len(2));
+    assert!(matches!(res, Err(Error::CollectionLengthExceeded { len: 3, limit: 2 })));
+    let map = serde_scale::to_vec(&vec![(1u8, 2u8), (3, 4), (5, 6)]).unwrap();
+    let res = serde_scale::from_slice_limited::<BTreeMap<u8, u8>>(&map, 2);
+    assert!(matches!(res, Err(Error::CollectionLengthExceeded { len: 3, limit: 2 })));
+    let string = serde_scale::to_vec(&"abc").unwrap();
+    let res = serde_scale::from_slice_limited::<String>(&string, 2);
+    assert!(matches!(res, Err(Error::CollectionLengthExceeded { len: 3, limit: 2 })));
+    assert_eq!(serde_scale::from_slice_limited::<String>(&string, 3).unwrap(), "abc");
+}
+
+#[test]
+fn max_len_rejects_hostile_length_before_allocating() {
+    let hostile = [0x07, 0, 0, 0, 0, 0x01];
+    let res = serde_scale::from_slice_limited::<Vec<u8>>(&hostile, 1 << 20);
+    assert!(matches!(
+        res,
+        Err(Error::CollectionLengthExceeded { len: 0x1_0000_0000, limit: 0x10_0000 })
+    ));
+}
+
+#[test]
+fn max_len_is_unset_by_default() {
+    assert_eq!(DeLimits::default().max_len, None);
+    let bytes = serde_scale::to_vec(&vec![0u16; 1000]).unwrap();
+    assert_eq!(serde_scale::from_slice::<Vec<u16>>(&bytes).unwrap().len(), 1000);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Operator {
+    name: String,
+    priority: u8,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Expression {
+    Const(i32),
+    Op(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[test]
+fn max_depth_stops_adversarial_recursion() {
+    let hostile = vec![1u8; 1_000_000];
+    let res = Expression::deserialize(&mut Deserializer::new(&hostile[..]).with_max_depth(128));
+    assert!(matches!(res, Err(Error::RecursionLimitExceeded { limit: 128 })));
+}
+
+#[test]
+fn max_depth_accepts_shallow_expressions() {
+    // Op(Const(2), "+", Const(3))
+    let bytes = [1, 0, 2, 0, 0, 0, 0x04, b'+', 2, 0, 3, 0, 0, 0];
+    let mut deserializer = Deserializer::new(&bytes[..]).with_max_depth(3);
+    let expr = Expression::deserialize(&mut deserializer).unwrap();
+    let expected = Expression::Op(
+        Box::new(Expression::Const(2)),
+        Operator { name: "+".into(), priority: 2 },
+        Box::new(Expression::Const(3)),
+    );
+    assert_eq!(expr, expected);
+    let res = Expression::deserialize(&mut Deserializer::new(&bytes[..]).with_max_depth(2));
+    assert!(matches!(res, Err(Error::RecursionLimitExceeded { limit: 2 })));
+}
@@ -0,0 +1,49 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{de::{DeserializeSeed, MapAccess}, Deserialize};
+use serde_scale::{DeLimits, Deserializer, Error};
+use std::{collections::BTreeMap, marker::PhantomData};
+
+/// Seed deserializing a value and scaling it
+struct Scaled(u32);
+
+impl<'de> DeserializeSeed<'de> for Scaled {
+    type Value = u32;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, d: D) -> Result<u32, D::Error> {
+        let v = PhantomData::<u32>.deserialize(d)?;
+        Ok(v * self.0)
+    }
+}
+
+fn sample() -> Vec<u8> {
+    let map = vec![(1u8, 10u32), (2, 20), (3, 30)].into_iter().collect::<BTreeMap<_, _>>();
+    let mut bytes = serde_scale::to_vec(&map).unwrap();
+    bytes.push(0xaa);
+    bytes
+}
+
+#[test]
+fn keys_and_values_can_be_pulled_manually() {
+    let bytes = sample();
+    let mut deserializer = Deserializer::new(&bytes[..]);
+    let mut entries = deserializer.map().unwrap();
+    assert_eq!(entries.remaining(), 3);
+    assert_eq!(entries.size_hint(), Some(3));
+    let mut pulled = Vec::new();
+    while let Some(key) = entries.next_key_seed(PhantomData::<u8>).unwrap() {
+        let value = entries.next_value_seed(Scaled(u32::from(key))).unwrap();
+        pulled.push((key, value));
+    }
+    assert_eq!(entries.remaining(), 0);
+    assert_eq!(pulled, [(1, 10), (2, 40), (3, 90)]);
+    assert_eq!(u8::deserialize(&mut deserializer).unwrap(), 0xaa);
+}
+
+#[test]
+fn map_access_enforces_limits() {
+    let bytes = sample();
+    let limits = DeLimits { max_map: Some(2), ..DeLimits::default() };
+    let mut deserializer = Deserializer::new(&bytes[..]).with_limits(limits);
+    assert!(matches!(deserializer.map(), Err(Error::MapLengthExceeded { len: 3, limit: 2 })));
+}
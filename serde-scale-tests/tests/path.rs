@@ -0,0 +1,30 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Entry {
+    #[serde(with = "serde_scale::path")]
+    path: PathBuf,
+}
+
+#[test]
+fn utf8_path_roundtrips() {
+    let entry = Entry { path: "/tmp/héllo/world.txt".into() };
+    let bytes = serde_scale::to_vec(&entry).unwrap();
+    assert_eq!(bytes, serde_scale::to_vec(&"/tmp/héllo/world.txt").unwrap());
+    assert_eq!(serde_scale::from_slice::<Entry>(&bytes).unwrap(), entry);
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_path_fails_with_clear_error() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let entry = Entry { path: OsStr::from_bytes(b"/tmp/\xff").into() };
+    match serde_scale::to_vec(&entry) {
+        Err(serde_scale::Error::Other(e)) => assert!(e.as_str().contains("not valid UTF-8")),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use std::{rc::Rc, sync::Arc};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Shared {
+    name: Arc<str>,
+    data: Arc<[u8]>,
+}
+
+#[test]
+fn arc_str_is_encoded_like_string() {
+    let s: Arc<str> = Arc::from("shared \u{1f600}");
+    assert_eq!(serde_scale::to_vec(&s).unwrap(), serde_scale::to_vec(&s.to_string()).unwrap());
+    let rc: Rc<str> = Rc::from("shared");
+    assert_eq!(serde_scale::to_vec(&rc).unwrap(), serde_scale::to_vec(&"shared").unwrap());
+}
+
+#[test]
+fn arc_bytes_are_encoded_like_vec() {
+    let data: Arc<[u8]> = Arc::from(vec![1u8; 70]);
+    assert_eq!(serde_scale::to_vec(&data).unwrap(), serde_scale::to_vec(&vec![1u8; 70]).unwrap());
+}
+
+#[test]
+fn shared_values_roundtrip() {
+    let value = Shared { name: Arc::from("name"), data: Arc::from(&[1u8, 2, 3][..]) };
+    let bytes = serde_scale::to_vec(&value).unwrap();
+    assert_eq!(bytes, serde_scale::to_vec(&("name", vec![1u8, 2, 3])).unwrap());
+    let read = serde_scale::from_slice::<Shared>(&bytes).unwrap();
+    assert_eq!(read, value);
+    assert_eq!(Arc::strong_count(&read.name), 1);
+    assert_eq!(&*read.data, [1, 2, 3]);
+}
@@ -0,0 +1,97 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use serde_scale::{Deserializer, EndOfInput, Error};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum OldMessage {
+    Ping(u8),
+    Resize { w: u32 },
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum NewMessage {
+    Ping(u8, #[serde(default)] u16),
+    Resize {
+        w: u32,
+        #[serde(default)]
+        h: u32,
+    },
+}
+
+fn from_slice_with_defaults<'a, T>(v: &'a [u8]) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(&mut Deserializer::new(v).with_default_trailing_fields())
+}
+
+#[test]
+fn tuple_variant_missing_trailing_field_uses_default() {
+    let bytes = serde_scale::to_vec(&OldMessage::Ping(5)).unwrap();
+    let msg = from_slice_with_defaults::<NewMessage>(&bytes).unwrap();
+    assert_eq!(msg, NewMessage::Ping(5, 0));
+}
+
+#[test]
+fn struct_variant_missing_trailing_field_uses_default() {
+    let bytes = [1, 3, 0, 0, 0];
+    let msg = from_slice_with_defaults::<NewMessage>(&bytes).unwrap();
+    assert_eq!(msg, NewMessage::Resize { w: 3, h: 0 });
+}
+
+#[test]
+fn complete_variant_is_unaffected() {
+    let bytes = serde_scale::to_vec(&NewMessage::Ping(5, 6)).unwrap();
+    let msg = from_slice_with_defaults::<NewMessage>(&bytes).unwrap();
+    assert_eq!(msg, NewMessage::Ping(5, 6));
+}
+
+#[test]
+fn missing_trailing_field_fails_by_default() {
+    let bytes = serde_scale::to_vec(&OldMessage::Ping(5)).unwrap();
+    let res = serde_scale::from_slice::<NewMessage>(&bytes);
+    assert!(matches!(res, Err(Error::Io(EndOfInput))));
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct OldRecord {
+    id: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct NewRecord {
+    id: u32,
+    note: Option<String>,
+}
+
+fn from_slice_with_none_at_end<'a, T>(v: &'a [u8]) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(&mut Deserializer::new(v).with_none_at_end_of_input())
+}
+
+#[test]
+fn missing_trailing_option_is_none() {
+    let bytes = serde_scale::to_vec(&OldRecord { id: 4 }).unwrap();
+    let record = from_slice_with_none_at_end::<NewRecord>(&bytes).unwrap();
+    assert_eq!(record, NewRecord { id: 4, note: None });
+    assert!(matches!(serde_scale::from_slice::<NewRecord>(&bytes), Err(Error::Io(EndOfInput))));
+}
+
+#[test]
+fn present_trailing_option_is_read() {
+    for note in [None, Some(String::from("new"))] {
+        let record = NewRecord { id: 4, note };
+        let bytes = serde_scale::to_vec(&record).unwrap();
+        assert_eq!(from_slice_with_none_at_end::<NewRecord>(&bytes).unwrap(), record);
+    }
+}
+
+#[test]
+fn option_cut_after_discriminant_still_fails() {
+    let bytes = [4, 0, 0, 0, 1];
+    let res = from_slice_with_none_at_end::<NewRecord>(&bytes);
+    assert!(matches!(res, Err(Error::Io(EndOfInput))));
+}
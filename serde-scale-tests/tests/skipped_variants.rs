@@ -0,0 +1,68 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use parity_scale_codec::Encode;
+use serde::{Deserialize, Serialize};
+use serde_scale::Error;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum SkippedLast {
+    First(u8),
+    Second(u16),
+    #[serde(skip)]
+    #[allow(dead_code)]
+    Internal(u32),
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum SkippedInMiddle {
+    First(u8),
+    #[serde(skip)]
+    #[allow(dead_code)]
+    Internal(u32),
+    Second(u16),
+    Third,
+}
+
+#[derive(Encode)]
+#[allow(dead_code)]
+enum CodecSkipped {
+    First(u8),
+    #[codec(skip)]
+    Internal(u32),
+    Second(u16),
+    Third,
+}
+
+#[test]
+fn trailing_skipped_variant_matches_codec() {
+    let value = SkippedLast::Second(0x0102);
+    let bytes = serde_scale::to_vec(&value).unwrap();
+    assert_eq!(bytes, CodecSkipped::Second(0x0102).encode());
+    assert_eq!(serde_scale::from_slice::<SkippedLast>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn skipped_variant_cannot_be_serialized() {
+    let res = serde_scale::to_vec(&SkippedLast::Internal(1));
+    assert!(matches!(res, Err(Error::Other(_))));
+}
+
+#[test]
+fn variants_after_skipped_one_serialize_with_declaration_index() {
+    assert_eq!(serde_scale::to_vec(&SkippedInMiddle::Second(1)).unwrap(), [2, 1, 0]);
+    assert_eq!(serde_scale::to_vec(&SkippedInMiddle::Third).unwrap(), [3]);
+    assert_eq!(CodecSkipped::Third.encode(), [2]);
+}
+
+#[test]
+fn variants_after_skipped_one_deserialize_like_codec() {
+    let bytes = CodecSkipped::Second(1).encode();
+    assert_eq!(
+        serde_scale::from_slice::<SkippedInMiddle>(&bytes).unwrap(),
+        SkippedInMiddle::Second(1),
+    );
+    assert_eq!(
+        serde_scale::from_slice::<SkippedInMiddle>(&CodecSkipped::Third.encode()).unwrap(),
+        SkippedInMiddle::Third,
+    );
+}
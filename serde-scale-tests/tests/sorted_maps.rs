@@ -0,0 +1,122 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::Serialize;
+use serde_scale::{Error, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+fn to_vec_sorted<T: Serialize>(x: &T) -> Vec<u8> {
+    let mut serializer = Serializer::new(Vec::new()).with_sorted_maps();
+    x.serialize(&mut serializer).unwrap();
+    serializer.into_inner()
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+struct Key {
+    name: String,
+    shard: u16,
+}
+
+fn keys() -> Vec<Key> {
+    (0..50u16)
+        .map(|i| Key { name: format!("key{}", i * 7 % 13), shard: i })
+        .collect()
+}
+
+#[test]
+fn struct_keyed_hash_map_serializes_deterministically() {
+    let forward = keys().into_iter().zip(0u32..50).collect::<HashMap<_, _>>();
+    let backward = keys().into_iter().zip(0u32..50).rev().collect::<HashMap<_, _>>();
+    let out = to_vec_sorted(&forward);
+    assert_eq!(out, to_vec_sorted(&backward));
+    assert_eq!(out, to_vec_sorted(&forward.clone()));
+}
+
+#[test]
+fn entries_are_sorted_by_serialized_key_bytes() {
+    let map = keys().into_iter().zip(0u32..50).collect::<HashMap<_, _>>();
+    let by_key_bytes = map
+        .iter()
+        .map(|(k, v)| (serde_scale::to_vec(k).unwrap(), *v))
+        .collect::<BTreeMap<_, _>>();
+    let mut expected = vec![(map.len() as u8) << 2];
+    for (key, value) in by_key_bytes {
+        expected.extend(key);
+        expected.extend(serde_scale::to_vec(&value).unwrap());
+    }
+    assert_eq!(to_vec_sorted(&map), expected);
+}
+
+#[test]
+fn nested_maps_are_sorted() {
+    let inner = (0..20u8).map(|i| (i, i)).collect::<HashMap<_, _>>();
+    let outer = (0..5u8).map(|i| (i, inner.clone())).collect::<HashMap<_, _>>();
+    let sorted = outer
+        .iter()
+        .map(|(k, v)| (*k, v.iter().map(|(a, b)| (*a, *b)).collect::<BTreeMap<_, _>>()))
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(to_vec_sorted(&outer), serde_scale::to_vec(&sorted).unwrap());
+}
+
+#[test]
+fn sorted_maps_preserve_byte_count() {
+    let map = keys().into_iter().zip(0u32..50).collect::<HashMap<_, _>>();
+    let mut serializer = Serializer::new(Vec::new()).with_sorted_maps();
+    map.serialize(&mut serializer).unwrap();
+    let (out, written) = serializer.finish();
+    assert_eq!(out.len(), written);
+    assert_eq!(out.len(), serde_scale::to_vec(&map).unwrap().len());
+}
+
+fn to_vec_sorted_by<T, K, F>(x: &T, cmp: F) -> Vec<u8>
+where
+    T: Serialize,
+    K: serde::de::DeserializeOwned,
+    F: Fn(&K, &K) -> std::cmp::Ordering + Send + Sync + 'static,
+{
+    let mut serializer = Serializer::new(Vec::new()).with_sorted_maps_by(cmp);
+    x.serialize(&mut serializer).unwrap();
+    serializer.into_inner()
+}
+
+#[test]
+fn entries_are_sorted_by_custom_comparator() {
+    let map = (0..300u32).step_by(7).map(|k| (k, k as u8)).collect::<HashMap<_, _>>();
+    let descending = map
+        .iter()
+        .map(|(k, v)| (std::cmp::Reverse(*k), *v))
+        .collect::<BTreeMap<_, _>>();
+    let out = to_vec_sorted_by(&map, |a: &u32, b: &u32| b.cmp(a));
+    assert_eq!(out, serde_scale::to_vec(&descending).unwrap());
+    let ascending = map.iter().map(|(k, v)| (*k, *v)).collect::<BTreeMap<_, _>>();
+    assert_eq!(to_vec_sorted_by(&map, u32::cmp), serde_scale::to_vec(&ascending).unwrap());
+    assert_ne!(to_vec_sorted(&map), serde_scale::to_vec(&ascending).unwrap());
+}
+
+#[test]
+fn equivalent_keys_are_ordered_by_bytes() {
+    let map = vec![("bb".to_owned(), 1u8), ("a".to_owned(), 2), ("c".to_owned(), 3)]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let out = to_vec_sorted_by(&map, |a: &String, b: &String| a.len().cmp(&b.len()));
+    assert_eq!(out, [3 << 2, 4, b'a', 2, 4, b'c', 3, 8, b'b', b'b', 1]);
+}
+
+#[test]
+fn maps_with_other_key_types_are_rejected() {
+    let inner = vec![("yy".to_owned(), 1u8), ("xx".to_owned(), 2)];
+    let inner = inner.into_iter().collect::<HashMap<_, _>>();
+    let outer = vec![(2u16, inner.clone()), (256, inner)].into_iter().collect::<HashMap<_, _>>();
+    let mut serializer = Serializer::new(Vec::new()).with_sorted_maps_by(u16::cmp);
+    let res = outer.serialize(&mut serializer);
+    assert!(matches!(res, Err(Error::Other(_))));
+}
+
+#[test]
+fn keys_are_read_back_with_serializer_options() {
+    let map = vec![(vec![9u8], 1u8), (vec![1, 2], 2)].into_iter().collect::<HashMap<_, _>>();
+    let mut serializer = Serializer::new(Vec::new())
+        .with_leb128_lengths()
+        .with_sorted_maps_by(|a: &Vec<u8>, b: &Vec<u8>| b.len().cmp(&a.len()));
+    map.serialize(&mut serializer).unwrap();
+    assert_eq!(serializer.into_inner(), [2, 2, 1, 2, 2, 1, 9, 1]);
+}
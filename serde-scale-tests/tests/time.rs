@@ -0,0 +1,58 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Timing {
+    #[serde(with = "serde_scale::duration")]
+    elapsed: Duration,
+    #[serde(with = "serde_scale::system_time")]
+    started: SystemTime,
+}
+
+fn roundtrip(timing: Timing) {
+    let bytes = serde_scale::to_vec(&timing).unwrap();
+    assert_eq!(bytes.len(), 24);
+    assert_eq!(serde_scale::from_slice::<Timing>(&bytes).unwrap(), timing);
+}
+
+#[test]
+fn duration_serializes_as_secs_then_nanos() {
+    let timing = Timing { elapsed: Duration::new(0x0102, 0x0304), started: UNIX_EPOCH };
+    let bytes = serde_scale::to_vec(&timing).unwrap();
+    assert_eq!(bytes[..12], [0x02, 0x01, 0, 0, 0, 0, 0, 0, 0x04, 0x03, 0, 0]);
+    assert_eq!(bytes[12..], [0; 12]);
+}
+
+#[test]
+fn time_before_epoch_has_negative_secs_and_positive_nanos() {
+    let started = UNIX_EPOCH - Duration::from_millis(1500);
+    let timing = Timing { elapsed: Duration::ZERO, started };
+    let bytes = serde_scale::to_vec(&timing).unwrap();
+    let mut expected = (-2i64).to_le_bytes().to_vec();
+    expected.extend(500_000_000u32.to_le_bytes());
+    assert_eq!(bytes[12..], expected[..]);
+}
+
+#[test]
+fn times_roundtrip() {
+    roundtrip(Timing { elapsed: Duration::MAX, started: SystemTime::now() });
+    roundtrip(Timing { elapsed: Duration::new(5, 999_999_999), started: UNIX_EPOCH });
+    roundtrip(Timing {
+        elapsed: Duration::from_nanos(1),
+        started: UNIX_EPOCH - Duration::new(1_000, 1),
+    });
+    roundtrip(Timing {
+        elapsed: Duration::ZERO,
+        started: UNIX_EPOCH - Duration::from_secs(3),
+    });
+}
+
+#[test]
+fn nanos_out_of_range_are_rejected() {
+    let mut bytes = serde_scale::to_vec(&Timing { elapsed: Duration::ZERO, started: UNIX_EPOCH })
+        .unwrap();
+    bytes[8..12].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+    assert!(serde_scale::from_slice::<Timing>(&bytes).is_err());
+}
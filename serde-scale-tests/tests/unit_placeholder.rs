@@ -0,0 +1,53 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_scale::{Deserializer, EndOfInput, Error, Serializer};
+
+fn to_vec_with_placeholder<T: Serialize>(x: &T) -> Vec<u8> {
+    let mut serializer = Serializer::new(Vec::new()).with_unit_placeholder();
+    x.serialize(&mut serializer).unwrap();
+    serializer.into_inner()
+}
+
+fn from_slice_with_placeholder<T: DeserializeOwned>(v: &[u8]) -> Result<T, Error<EndOfInput>> {
+    T::deserialize(&mut Deserializer::new(v).with_unit_placeholder())
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Marker;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Record {
+    a: u8,
+    reserved: (),
+    marker: Marker,
+    b: u8,
+}
+
+fn record() -> Record {
+    Record { a: 1, reserved: (), marker: Marker, b: 2 }
+}
+
+#[test]
+fn units_are_empty_by_default() {
+    assert_eq!(serde_scale::to_vec(&()).unwrap(), []);
+    assert_eq!(serde_scale::to_vec(&record()).unwrap(), [1, 2]);
+    assert_eq!(serde_scale::from_slice::<Record>(&[1, 2]).unwrap(), record());
+}
+
+#[test]
+fn units_are_zero_bytes_with_placeholder() {
+    assert_eq!(to_vec_with_placeholder(&()), [0]);
+    assert_eq!(to_vec_with_placeholder(&Marker), [0]);
+    let bytes = to_vec_with_placeholder(&record());
+    assert_eq!(bytes, [1, 0, 0, 2]);
+    assert_eq!(from_slice_with_placeholder::<Record>(&bytes).unwrap(), record());
+}
+
+#[test]
+fn non_zero_placeholder_is_rejected() {
+    assert!(matches!(
+        from_slice_with_placeholder::<Record>(&[1, 0, 7, 2]),
+        Err(Error::InvalidUnitPlaceholder { found: 7 }),
+    ));
+}
@@ -0,0 +1,30 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Record {
+    #[serde(with = "serde_scale::uuid")]
+    id: Uuid,
+    tag: u8,
+}
+
+#[test]
+fn uuid_serializes_as_16_big_endian_bytes() {
+    let id = Uuid::from_u128(0x0011_2233_4455_6677_8899_aabb_ccdd_eeff);
+    let bytes = serde_scale::to_vec(&Record { id, tag: 7 }).unwrap();
+    let mut expected = id.as_bytes().to_vec();
+    expected.push(7);
+    assert_eq!(bytes, expected);
+    assert_eq!(bytes[..2], [0x00, 0x11]);
+}
+
+#[test]
+fn uuid_roundtrips() {
+    for id in [Uuid::nil(), Uuid::max(), Uuid::from_u128(0x1234)] {
+        let record = Record { id, tag: 1 };
+        let bytes = serde_scale::to_vec(&record).unwrap();
+        assert_eq!(serde_scale::from_slice::<Record>(&bytes).unwrap(), record);
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Bulk32(#[serde(with = "serde_scale::vec_of_le_ints")] Vec<u32>);
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Bulk64(#[serde(with = "serde_scale::vec_of_le_ints")] Vec<u64>);
+
+fn values_32() -> Vec<u32> {
+    vec![0, 1, 0x0102_0304, u32::MAX, 42]
+}
+
+fn values_64() -> Vec<u64> {
+    vec![0, 1, 0x0102_0304_0506_0708, u64::MAX, 42]
+}
+
+#[test]
+fn bulk_u32_serializes_like_vec() {
+    let values = values_32();
+    assert_eq!(
+        serde_scale::to_vec(&Bulk32(values.clone())).unwrap(),
+        serde_scale::to_vec(&values).unwrap(),
+    );
+}
+
+#[test]
+fn bulk_u64_serializes_like_vec() {
+    let values = values_64();
+    assert_eq!(
+        serde_scale::to_vec(&Bulk64(values.clone())).unwrap(),
+        serde_scale::to_vec(&values).unwrap(),
+    );
+}
+
+#[test]
+fn bulk_u32_deserializes_like_vec_at_any_alignment() {
+    let encoded = serde_scale::to_vec(&values_32()).unwrap();
+    for padding in 0..8 {
+        let mut buf = vec![0xff; padding];
+        buf.extend_from_slice(&encoded);
+        let bulk = serde_scale::from_slice::<Bulk32>(&buf[padding..]).unwrap();
+        let generic = serde_scale::from_slice::<Vec<u32>>(&buf[padding..]).unwrap();
+        assert_eq!(bulk.0, generic);
+    }
+}
+
+#[test]
+fn bulk_u64_deserializes_like_vec_at_any_alignment() {
+    let encoded = serde_scale::to_vec(&values_64()).unwrap();
+    for padding in 0..8 {
+        let mut buf = vec![0xff; padding];
+        buf.extend_from_slice(&encoded);
+        let bulk = serde_scale::from_slice::<Bulk64>(&buf[padding..]).unwrap();
+        let generic = serde_scale::from_slice::<Vec<u64>>(&buf[padding..]).unwrap();
+        assert_eq!(bulk.0, generic);
+    }
+}
+
+#[test]
+fn bulk_deserializes_empty_vec() {
+    let encoded = serde_scale::to_vec(&Vec::<u32>::new()).unwrap();
+    assert_eq!(serde_scale::from_slice::<Bulk32>(&encoded).unwrap(), Bulk32(Vec::new()));
+}
+
+#[test]
+fn bulk_fails_on_truncated_input() {
+    let encoded = serde_scale::to_vec(&values_32()).unwrap();
+    let res = serde_scale::from_slice::<Bulk32>(&encoded[..encoded.len() - 1]);
+    assert!(matches!(res, Err(serde_scale::Error::Io(serde_scale::EndOfInput))));
+}
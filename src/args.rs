@@ -0,0 +1,69 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Error, Serializer, Write};
+use serde::Serialize;
+
+/// Serializes values of different types one after the other, like the fields of a tuple
+///
+/// No length prefix is written, so the output is identical to the serialization of a tuple
+/// holding the same values. This is useful when the values are only known at runtime, e.g. the
+/// arguments of a function call.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde_scale::ArgsBuilder;
+///
+/// let mut args = ArgsBuilder::new(Vec::new());
+/// args.push(&3u8).unwrap().push("foo").unwrap();
+/// assert_eq!(args.finish(), serde_scale::to_vec(&(3u8, "foo")).unwrap());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ArgsBuilder<W> {
+    serializer: Serializer<W>,
+}
+
+impl<W: Write> ArgsBuilder<W> {
+    /// Returns a builder writing to the given writer
+    pub fn new(out: W) -> Self {
+        Self {
+            serializer: Serializer::new(out),
+        }
+    }
+
+    /// Serializes the next value
+    pub fn push<T>(&mut self, value: &T) -> Result<&mut Self, Error<W::Error>>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut self.serializer)?;
+        Ok(self)
+    }
+
+    /// Returns the underlying writer
+    pub fn finish(self) -> W {
+        self.serializer.into_inner()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{to_vec, ArgsBuilder};
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn args_serialize_like_tuple() {
+        let mut args = ArgsBuilder::new(Vec::new());
+        args.push(&7u8).unwrap();
+        args.push(&String::from("foo")).unwrap();
+        args.push(&true).unwrap();
+        let expected = to_vec(&(7u8, String::from("foo"), true)).unwrap();
+        assert_eq!(args.finish(), expected);
+    }
+
+    #[test]
+    fn no_args_serialize_like_unit() {
+        let args = ArgsBuilder::new(Vec::new());
+        assert_eq!(args.finish(), to_vec(&()).unwrap());
+    }
+}
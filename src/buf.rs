@@ -0,0 +1,116 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Bytes, EndOfInput, Read, Write};
+use alloc::vec::Vec;
+use bytes::{Buf, BufMut};
+use core::convert::Infallible;
+
+/// Adapter to use a [`bytes::Buf`] as a [`Read`]
+///
+/// Bytes are yielded as [`Bytes::Temporary`] straight from the current chunk of the buffer when
+/// possible. Spans crossing chunk boundaries are copied into a scratch buffer first.
+///
+/// ```rust
+/// use bytes::{Buf, Bytes};
+/// use serde::Deserialize;
+/// use serde_scale::{BytesReader, Deserializer};
+///
+/// let bytes = serde_scale::to_vec(&(1u16, "split")).unwrap();
+/// let (head, tail) = bytes.split_at(4);
+/// let chain = Bytes::copy_from_slice(head).chain(Bytes::copy_from_slice(tail));
+/// let mut deserializer = Deserializer::new(BytesReader::new(chain));
+/// let value = <(u16, String)>::deserialize(&mut deserializer).unwrap();
+/// assert_eq!(value, (1, String::from("split")));
+/// ```
+#[derive(Debug)]
+pub struct BytesReader<B> {
+    inner: B,
+    scratch: Vec<u8>,
+}
+
+impl<B> BytesReader<B> {
+    /// Returns a reader consuming `inner`
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying buffer, advanced past the bytes read
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<'a, B: Buf> Read<'a> for BytesReader<B> {
+    type Error = EndOfInput;
+
+    fn read_map<R, F>(&mut self, n: usize, f: F) -> Result<R, Self::Error>
+    where
+        F: FnOnce(Bytes<'a, '_>) -> R,
+    {
+        if self.inner.remaining() < n {
+            return Err(EndOfInput);
+        }
+        let chunk = self.inner.chunk();
+        if chunk.len() >= n {
+            let res = f(Bytes::Temporary(&chunk[..n]));
+            self.inner.advance(n);
+            return Ok(res);
+        }
+        self.scratch.clear();
+        self.scratch.resize(n, 0);
+        self.inner.copy_to_slice(&mut self.scratch);
+        Ok(f(Bytes::Temporary(&self.scratch)))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        if self.inner.remaining() < buf.len() {
+            return Err(EndOfInput);
+        }
+        self.inner.copy_to_slice(buf);
+        Ok(())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.inner.remaining())
+    }
+}
+
+/// Adapter to use a [`bytes::BufMut`] as a [`Write`]
+///
+/// Bytes are appended with [`BufMut::put_slice`]. Buffers growing on demand like `BytesMut` and
+/// `Vec<u8>` never fail, while writing past the end of a fixed-size buffer panics.
+///
+/// ```rust
+/// use bytes::BytesMut;
+/// use serde_scale::BytesWriter;
+///
+/// let mut buf = BytesMut::new();
+/// serde_scale::to_writer(BytesWriter::new(&mut buf), &(1u16, "put")).unwrap();
+/// assert_eq!(buf.freeze(), serde_scale::to_vec(&(1u16, "put")).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct BytesWriter<B>(B);
+
+impl<B> BytesWriter<B> {
+    /// Returns a writer appending to `inner`
+    pub fn new(inner: B) -> Self {
+        Self(inner)
+    }
+
+    /// Returns the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: BufMut> Write for BytesWriter<B> {
+    type Error = Infallible;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.put_slice(data);
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `DateTime<Utc>` as milliseconds since the Unix epoch
+//!
+//! A time is encoded as the signed number of milliseconds since 1970-01-01T00:00:00Z as an `i64`,
+//! i.e. 8 bytes. This bypasses the default `serde` representation of `chrono` types, which is an
+//! RFC 3339 string. Sub-millisecond precision is dropped when serializing, rounding towards the
+//! past, so a time 1.5 ms before the epoch is encoded as -2. A leap second is encoded as the
+//! following second. Deserialization fails if the time is out of the range of `DateTime<Utc>`.
+//!
+//! ```rust
+//! use chrono::{DateTime, Utc};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Event {
+//!     #[serde(with = "serde_scale::chrono")]
+//!     at: DateTime<Utc>,
+//! }
+//!
+//! let event = Event { at: DateTime::from_timestamp_millis(0x0102).unwrap() };
+//! let bytes = serde_scale::to_vec(&event).unwrap();
+//! assert_eq!(bytes, [0x02, 0x01, 0, 0, 0, 0, 0, 0]);
+//! assert_eq!(serde_scale::from_slice::<Event>(&bytes).unwrap(), event);
+//! ```
+
+use ::chrono::{DateTime, Utc};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes a time as milliseconds since the Unix epoch (`i64`)
+pub fn serialize<S>(t: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    t.timestamp_millis().serialize(serializer)
+}
+
+/// Deserializes a time from milliseconds since the Unix epoch (`i64`)
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let millis = i64::deserialize(deserializer)?;
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| D::Error::custom("Time is out of the range of DateTime<Utc>"))
+}
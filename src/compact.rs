@@ -0,0 +1,184 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! Compact encoding of unsigned integers
+//!
+//! SCALE only defines the compact encoding for unsigned integers, so [`Compact`] can only be
+//! serialized when wrapping `u8`, `u16`, `u32`, `u64` or `u128`. Attempting to compact-encode a signed
+//! integer does not compile:
+//!
+//! ```compile_fail
+//! serde_scale::to_vec(&serde_scale::Compact(-1i32));
+//! ```
+//!
+//! Fields can also be compact-encoded without changing their type by using this module with
+//! `#[serde(with = "serde_scale::compact")]`:
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")] {
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Balance {
+//!     #[serde(with = "serde_scale::compact")]
+//!     amount: u128,
+//! }
+//!
+//! let balance = Balance { amount: 1 };
+//! let bytes = serde_scale::to_vec(&balance).unwrap();
+//! assert_eq!(bytes, [4]);
+//! assert_eq!(serde_scale::from_slice::<Balance>(&bytes).unwrap(), balance);
+//! # }
+//! ```
+
+use crate::{Serializer, Write};
+use core::{convert::TryFrom, fmt, marker::PhantomData};
+use serde::{
+    de::{Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+/// Unsigned integer serialized with the SCALE compact encoding
+///
+/// With other serializers, the integer is serialized as is.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde_scale::Compact;
+///
+/// assert_eq!(serde_scale::to_vec(&Compact(1u32)).unwrap(), [4]);
+/// assert_eq!(serde_scale::from_slice::<Compact<u32>>(&[4]).unwrap(), Compact(1));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Compact<T>(pub T);
+
+/// Unsigned integer types supporting the compact encoding
+pub trait CompactInt:
+    sealed::Sealed + Copy + Into<u128> + TryFrom<u128> + Serialize + for<'de> Deserialize<'de>
+{
+}
+
+impl CompactInt for u8 {}
+impl CompactInt for u16 {}
+impl CompactInt for u32 {}
+impl CompactInt for u64 {}
+impl CompactInt for u128 {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+    impl Sealed for u128 {}
+}
+
+pub(crate) const NAME: &str = "$serde_scale::Compact";
+
+impl<T: CompactInt> Serialize for Compact<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &self.0)
+    }
+}
+
+impl<'de, T: CompactInt> Deserialize<'de> for Compact<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, CompactVisitor(PhantomData))
+    }
+}
+
+struct CompactVisitor<T>(PhantomData<T>);
+
+impl<'de, T: CompactInt> Visitor<'de> for CompactVisitor<T> {
+    type Value = Compact<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a compact unsigned integer")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        T::try_from(v.into())
+            .map(Compact)
+            .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        T::try_from(v)
+            .map(Compact)
+            .map_err(|_| E::invalid_value(Unexpected::Other("128-bit integer"), &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Compact)
+    }
+}
+
+/// Serializes an unsigned integer with the compact encoding
+///
+/// Meant to be used with `#[serde(with = "serde_scale::compact")]`.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: CompactInt,
+    S: serde::Serializer,
+{
+    Compact(*value).serialize(serializer)
+}
+
+/// Deserializes an unsigned integer with the compact encoding
+///
+/// Meant to be used with `#[serde(with = "serde_scale::compact")]`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: CompactInt,
+    D: Deserializer<'de>,
+{
+    Compact::<T>::deserialize(deserializer).map(|Compact(v)| v)
+}
+
+/// Returns the value of the unsigned integer serialized by `value`
+pub(crate) fn unsigned_value<T>(value: &T) -> Option<u128>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(Scratch::default());
+    value.serialize(&mut serializer).ok()?;
+    let scratch = serializer.into_inner();
+    let mut bytes = [0; 16];
+    bytes[..scratch.len].copy_from_slice(&scratch.buf[..scratch.len]);
+    Some(u128::from_le_bytes(bytes))
+}
+
+#[derive(Default)]
+struct Scratch {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl Write for Scratch {
+    type Error = &'static str;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let dst = self.buf
+            .get_mut(self.len..self.len + data.len())
+            .ok_or("Value too large for compact encoding")?;
+        dst.copy_from_slice(data);
+        self.len += data.len();
+        Ok(())
+    }
+}
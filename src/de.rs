@@ -1,78 +1,807 @@
 // Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
 
-use core::convert::TryFrom;
-use crate::{Bytes, EndOfInput, Error, Read};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug},
+};
+use crate::{Bytes, EndOfInput, Error, FramedReader, PathSegment, Read};
+#[cfg(feature = "alloc")]
+use crate::OtherError;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::BTreeSet, vec::Vec};
 use serde::{
-    de::{DeserializeSeed, Visitor},
-    Deserialize, Deserializer as _,
+    de::{value::U8Deserializer, DeserializeSeed, IntoDeserializer, Visitor},
+    Deserialize,
 };
 
 /// Deserializes a value encoded with SCALE
+///
+/// Strings and byte arrays can be borrowed from `v` (e.g. as `&'a str` or `&'a [u8]`) without
+/// copying, in which case the deserialized value cannot outlive `v`:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// let bytes = serde_scale::to_vec(&"hello").unwrap();
+/// let s: &str = serde_scale::from_slice(&bytes).unwrap();
+/// assert_eq!(s, "hello");
+/// # }
+/// ```
+///
+/// Dropping the buffer while the value is still in use does not compile:
+///
+/// ```compile_fail
+/// let s: &str = {
+///     let bytes = serde_scale::to_vec(&"hello").unwrap();
+///     serde_scale::from_slice(&bytes).unwrap()
+/// };
+/// assert_eq!(s, "hello");
+/// ```
 pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T, Error<EndOfInput>>
 where
     T: Deserialize<'a>,
 {
-    T::deserialize(&mut Deserializer(v))
+    T::deserialize(&mut Deserializer::new(v))
+}
+
+/// Deserializes a value encoded with SCALE, rejecting collections longer than `max_len`
+///
+/// This is a shorthand for a [`Deserializer`] configured with
+/// [`with_max_len`](Deserializer::with_max_len), meant for untrusted input.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde_scale::{from_slice_limited, Error};
+///
+/// let hostile = [0x03, 0, 0, 0, 0x40];
+/// let res = from_slice_limited::<Vec<u64>>(&hostile, 1024);
+/// assert!(matches!(res, Err(Error::CollectionLengthExceeded { len: 0x4000_0000, limit: 1024 })));
+/// # }
+/// ```
+pub fn from_slice_limited<'a, T>(v: &'a [u8], max_len: usize) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(&mut Deserializer::new(v).with_max_len(max_len))
+}
+
+/// Deserializes a value encoded with SCALE, requiring all of `v` to be consumed
+///
+/// Unlike [`from_slice`], which ignores bytes following the value, this fails with
+/// [`Error::TrailingBytes`] if any are left, which helps catching truncation and framing bugs.
+///
+/// ```rust
+/// use serde_scale::{from_slice_strict, Error};
+///
+/// assert_eq!(from_slice_strict::<u16>(&[1, 0]).unwrap(), 1);
+/// let res = from_slice_strict::<u16>(&[1, 0, 0]);
+/// assert!(matches!(res, Err(Error::TrailingBytes { remaining: 1 })));
+/// ```
+pub fn from_slice_strict<'a, T>(v: &'a [u8]) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(v);
+    let value = T::deserialize(&mut deserializer)?;
+    match deserializer.into_inner().len() {
+        0 => Ok(value),
+        remaining => Err(Error::TrailingBytes { remaining }),
+    }
+}
+
+/// Deserializes a value encoded with SCALE and advances `v` past the bytes consumed
+///
+/// This allows reading consecutive values from the same buffer. `v` is left unchanged if
+/// deserialization fails.
+///
+/// ```rust
+/// let bytes = [1, 0, 2, 0, 0, 0];
+/// let mut input = &bytes[..];
+/// assert_eq!(serde_scale::from_slice_advancing::<u16>(&mut input).unwrap(), 1);
+/// assert_eq!(input, [2, 0, 0, 0]);
+/// ```
+pub fn from_slice_advancing<'a, T>(v: &mut &'a [u8]) -> Result<T, Error<EndOfInput>>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(*v);
+    let value = T::deserialize(&mut deserializer)?;
+    *v = deserializer.into_inner();
+    Ok(value)
+}
+
+/// Deserializes a value encoded with SCALE from a [`std::io::Read`]
+///
+/// Nothing can be borrowed from a stream, so `T` must own its data. Reaching the end of the
+/// stream before the value is complete fails with an [`Error::Io`] of kind
+/// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof). Bytes following the value are left in
+/// the stream, although some may have been consumed if `r` is buffered.
+///
+/// ```rust
+/// let bytes = serde_scale::to_vec(&(7u8, "seven")).unwrap();
+/// let value: (u8, String) = serde_scale::from_reader(std::io::Cursor::new(bytes)).unwrap();
+/// assert_eq!(value, (7, String::from("seven")));
+/// ```
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(r: R) -> Result<T, Error<std::io::Error>>
+where
+    R: std::io::Read,
+    T: serde::de::DeserializeOwned,
+{
+    T::deserialize(&mut Deserializer::new(crate::IoReader::new(r)))
+}
+
+/// Limits enforced by the deserializer to protect against hostile input
+///
+/// `None` means unlimited, which is the default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DeLimits {
+    /// Maximum number of elements in a sequence
+    pub max_seq: Option<usize>,
+    /// Maximum number of entries in a map
+    pub max_map: Option<usize>,
+    /// Maximum number of bytes in a string or byte array
+    pub max_bytes: Option<usize>,
+    /// Maximum length of any sequence, map, string or byte array
+    ///
+    /// This applies on top of the limits specific to each kind of collection.
+    pub max_len: Option<usize>,
+    /// Maximum nesting of sequences, tuples, structures, maps and enumerations
+    pub max_depth: Option<usize>,
 }
 
 /// Deserializer for the SCALE encoding
-pub struct Deserializer<R>(R);
+pub struct Deserializer<R> {
+    reader: R,
+    /// Number of bytes read
+    position: usize,
+    limits: DeLimits,
+    depth: usize,
+    default_trailing_fields: bool,
+    none_at_end: bool,
+    leb128_lengths: bool,
+    unit_placeholder: bool,
+    #[cfg(feature = "alloc")]
+    length_validator: Option<Box<LengthValidator>>,
+    #[cfg(feature = "alloc")]
+    unique_map_keys: bool,
+    /// Bytes read since capture started, used to compare map keys
+    #[cfg(feature = "alloc")]
+    captured: Option<Vec<u8>>,
+    /// Number of bytes left in the [`Nested`](crate::Nested) value being deserialized
+    #[cfg(feature = "alloc")]
+    nested_left: Option<usize>,
+    /// Location of the value being deserialized, if errors report it, with the position at which
+    /// each segment starts
+    #[cfg(feature = "alloc")]
+    path: Option<Vec<(PathSegment, usize)>>,
+}
+
+#[cfg(feature = "alloc")]
+type LengthValidator = dyn FnMut(u64) -> Result<(), OtherError>;
 
 impl<'de, R: Read<'de>> Deserializer<R> {
     /// Returns a deserializer using the given reader
     pub fn new(r: R) -> Self {
-        Self(r)
+        Self {
+            reader: r,
+            position: 0,
+            limits: DeLimits::default(),
+            depth: 0,
+            default_trailing_fields: false,
+            none_at_end: false,
+            leb128_lengths: false,
+            unit_placeholder: false,
+            #[cfg(feature = "alloc")]
+            length_validator: None,
+            #[cfg(feature = "alloc")]
+            unique_map_keys: false,
+            #[cfg(feature = "alloc")]
+            captured: None,
+            #[cfg(feature = "alloc")]
+            nested_left: None,
+            #[cfg(feature = "alloc")]
+            path: None,
+        }
+    }
+
+    /// Returns a deserializer enforcing the given limits
+    pub fn with_limits(self, limits: DeLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Returns a deserializer rejecting any sequence, map, string or byte array longer than `limit`
+    ///
+    /// Lengths are checked as soon as they are decoded, so a hostile input claiming a huge length
+    /// fails with [`Error::CollectionLengthExceeded`] before anything is allocated. This sets
+    /// [`DeLimits::max_len`] and keeps the other limits.
+    pub fn with_max_len(self, limit: usize) -> Self {
+        Self {
+            limits: DeLimits {
+                max_len: Some(limit),
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    /// Returns a deserializer rejecting values nested more than `limit` levels deep
+    ///
+    /// Each sequence, tuple, structure, map and enumeration being deserialized counts as one
+    /// level. Going deeper fails with [`Error::RecursionLimitExceeded`], which protects recursive
+    /// types against adversarial input that would otherwise overflow the stack. This sets
+    /// [`DeLimits::max_depth`] and keeps the other limits.
+    pub fn with_max_depth(self, limit: usize) -> Self {
+        Self {
+            limits: DeLimits {
+                max_depth: Some(limit),
+                ..self.limits
+            },
+            ..self
+        }
+    }
+
+    /// Returns a deserializer tolerating enumeration variants missing trailing fields
+    ///
+    /// When the input ends before all the fields of a tuple or struct variant are read, the
+    /// missing fields take their default value instead of causing an error. This allows reading
+    /// data written before fields were appended to a variant. The missing fields must be marked
+    /// with `#[serde(default)]`.
+    ///
+    /// This only applies to readers able to tell that the end of the input was reached (see
+    /// [`Read::remaining`]).
+    pub fn with_default_trailing_fields(self) -> Self {
+        Self {
+            default_trailing_fields: true,
+            ..self
+        }
+    }
+
+    /// Returns a deserializer reading options as `None` once the input is exhausted
+    ///
+    /// This allows reading data written before an optional field was appended to a structure:
+    /// the missing field is `None` instead of causing an error. Input that does end in the middle
+    /// of an option is still reported as such.
+    ///
+    /// This only applies to readers able to tell that the end of the input was reached (see
+    /// [`Read::remaining`]).
+    pub fn with_none_at_end_of_input(self) -> Self {
+        Self {
+            none_at_end: true,
+            ..self
+        }
+    }
+
+    /// Returns a deserializer expecting collection lengths encoded as unsigned LEB128
+    ///
+    /// This is the counterpart of
+    /// [`Serializer::with_leb128_lengths`](crate::Serializer::with_leb128_lengths) and deviates
+    /// from SCALE in the same way.
+    pub fn with_leb128_lengths(self) -> Self {
+        Self {
+            leb128_lengths: true,
+            ..self
+        }
+    }
+
+    /// Returns a deserializer expecting a single 0 byte for `()` and unit structs
+    ///
+    /// This is the counterpart of
+    /// [`Serializer::with_unit_placeholder`](crate::Serializer::with_unit_placeholder).
+    pub fn with_unit_placeholder(self) -> Self {
+        Self {
+            unit_placeholder: true,
+            ..self
+        }
+    }
+
+    /// Returns a deserializer calling `f` with every decoded length before using it
+    ///
+    /// `f` sees the lengths of sequences, maps, strings and byte arrays before anything is
+    /// allocated for them, which allows enforcing custom policies. An error returned by `f` aborts
+    /// deserialization with [`Error::Other`].
+    #[cfg(feature = "alloc")]
+    pub fn with_length_validator<F>(self, f: F) -> Self
+    where
+        F: FnMut(u64) -> Result<(), OtherError> + 'static,
+    {
+        Self {
+            length_validator: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Returns a deserializer rejecting maps with duplicate keys
+    ///
+    /// Collections like `HashMap` or `BTreeMap` silently keep a single entry when a key is
+    /// repeated, which may hide data in untrusted input. With this option, deserializing a map
+    /// whose keys are not all distinct fails with [`Error::DuplicateMapKey`]. Keys are compared by
+    /// their encoded bytes, so this works for any key type.
+    #[cfg(feature = "alloc")]
+    pub fn with_unique_map_keys(self) -> Self {
+        Self {
+            unique_map_keys: true,
+            ..self
+        }
+    }
+
+    /// Returns a deserializer reporting where errors occur within the value
+    ///
+    /// The deserializer keeps track of the fields, indices and enumeration variants it descends
+    /// through, and errors are wrapped in [`Error::WithPath`] along with the location of the
+    /// innermost value that failed and the offset of its first byte in the input. This makes
+    /// decoding failures in nested types easier to debug. Errors in the top-level value itself are
+    /// not wrapped.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use serde_scale::Deserializer;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Flags {
+    ///     values: Vec<bool>,
+    /// }
+    ///
+    /// let bytes = [0x0c, 1, 0, 2];
+    /// let mut deserializer = Deserializer::new(&bytes[..]).with_error_path();
+    /// let e = Flags::deserialize(&mut deserializer).unwrap_err();
+    /// assert_eq!(e.to_string(), "Expected boolean (0 or 1), found 2 at .values[2]");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn with_error_path(self) -> Self {
+        Self {
+            path: Some(Vec::new()),
+            ..self
+        }
+    }
+
+    /// Returns the limits enforced by this deserializer
+    pub fn limits(&self) -> &DeLimits {
+        &self.limits
+    }
+
+    /// Returns the number of bytes left in the input if the reader knows it
+    ///
+    /// This allows checking for more values after deserializing one. Readers that cannot know how
+    /// many bytes are left, e.g. streams, return `None` (see [`Read::remaining`]).
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use serde_scale::Deserializer;
+    ///
+    /// let bytes = [1, 0, 2, 0];
+    /// let mut deserializer = Deserializer::new(&bytes[..]);
+    /// assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 1);
+    /// assert_eq!(deserializer.remaining(), Some(2));
+    /// assert!(!deserializer.is_empty());
+    /// assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 2);
+    /// assert!(deserializer.is_empty());
+    /// ```
+    pub fn remaining(&self) -> Option<usize> {
+        #[cfg(feature = "alloc")]
+        if let Some(left) = self.nested_left {
+            return Some(self.reader.remaining().map_or(left, |n| n.min(left)));
+        }
+        self.reader.remaining()
+    }
+
+    /// Returns the number of bytes read so far
+    ///
+    /// This helps locating malformed input. After a failure, it is the offset just past the
+    /// bytes read for the value that failed, e.g. past the invalid byte of a boolean. A read that
+    /// failed does not count.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use serde_scale::{Deserializer, Error};
+    ///
+    /// let bytes = [1, 0, 1, 2];
+    /// let mut deserializer = Deserializer::new(&bytes[..]);
+    /// let res = <(u16, bool, bool)>::deserialize(&mut deserializer);
+    /// assert!(matches!(res, Err(Error::ExpectedBoolean { found: 2 })));
+    /// assert_eq!(deserializer.position(), 4);
+    /// ```
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns whether the input is known to be exhausted
+    ///
+    /// This is `false` for readers that cannot know how many bytes are left, even once their
+    /// input is exhausted.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+
+    /// Reads the length of a map and returns access to its entries
+    ///
+    /// This allows pulling the keys and values of a map one at a time with
+    /// [`serde::de::MapAccess`] instead of deserializing the whole map at once. The limit on the
+    /// number of entries applies.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use serde::de::MapAccess;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map = vec![(1u8, 10u32), (2, 20)].into_iter().collect::<BTreeMap<_, _>>();
+    /// let bytes = serde_scale::to_vec(&map).unwrap();
+    /// let mut deserializer = serde_scale::Deserializer::new(&bytes[..]);
+    /// let mut entries = deserializer.map().unwrap();
+    /// let mut sum = 0;
+    /// while let Some(key) = entries.next_key::<u8>().unwrap() {
+    ///     sum += u32::from(key) * entries.next_value::<u32>().unwrap();
+    /// }
+    /// assert_eq!(sum, 50);
+    /// # }
+    /// ```
+    pub fn map(&mut self) -> Result<Map<'_, R>, Error<R::Error>> {
+        let remaining = self.read_map_len()?;
+        Ok(Map::new(self, remaining))
+    }
+
+    /// Deserializes exactly `N` values with no length prefix
+    ///
+    /// This reads the same bytes as deserializing a `[T; N]` but is not restricted to the array
+    /// lengths supported by `serde`.
+    pub fn read_array<T, const N: usize>(&mut self) -> Result<[T; N], Error<R::Error>>
+    where
+        T: Deserialize<'de>,
+    {
+        let mut out: [Option<T>; N] = core::array::from_fn(|_| None);
+        for slot in &mut out {
+            *slot = Some(T::deserialize(&mut *self)?);
+        }
+        Ok(out.map(|x| x.expect("All elements were deserialized")))
+    }
+
+    /// Returns a deserializer limited to the next `len` bytes, which must all be consumed
+    ///
+    /// This wraps the reader in an [`exact`](FramedReader::exact) [`FramedReader`] and keeps the
+    /// options of this deserializer. Reading past the frame fails with
+    /// [`FrameError::EndOfFrame`](crate::FrameError::EndOfFrame), and
+    /// [`FramedReader::finish`] reports bytes left in the frame once the value is deserialized.
+    /// This catches inner lengths disagreeing with their content, e.g. in double-encoded values.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use serde_scale::{Compact, Deserializer};
+    ///
+    /// let input = [8, 1, 0, 9];
+    /// let mut deserializer = Deserializer::new(&input[..]);
+    /// let Compact(len) = Compact::<u32>::deserialize(&mut deserializer).unwrap();
+    /// let mut framed = deserializer.with_exact_frame(len as usize);
+    /// assert_eq!(u16::deserialize(&mut framed).unwrap(), 1);
+    /// assert_eq!(framed.into_inner().finish().unwrap(), [9]);
+    /// ```
+    pub fn with_exact_frame(self, len: usize) -> Deserializer<FramedReader<R>> {
+        self.map_reader(|r| FramedReader::new(r, len).exact())
     }
 
     /// Returns the underlying reader
     pub fn into_inner(self) -> R {
-        self.0
+        self.reader
+    }
+
+    /// Returns a deserializer with the same options and state reading from `f(reader)`
+    fn map_reader<R2, F>(self, f: F) -> Deserializer<R2>
+    where
+        F: FnOnce(R) -> R2,
+    {
+        Deserializer {
+            reader: f(self.reader),
+            position: self.position,
+            limits: self.limits,
+            depth: self.depth,
+            default_trailing_fields: self.default_trailing_fields,
+            none_at_end: self.none_at_end,
+            leb128_lengths: self.leb128_lengths,
+            unit_placeholder: self.unit_placeholder,
+            #[cfg(feature = "alloc")]
+            length_validator: self.length_validator,
+            #[cfg(feature = "alloc")]
+            unique_map_keys: self.unique_map_keys,
+            #[cfg(feature = "alloc")]
+            captured: self.captured,
+            #[cfg(feature = "alloc")]
+            nested_left: self.nested_left,
+            #[cfg(feature = "alloc")]
+            path: self.path,
+        }
     }
 
-    fn read_compact(&mut self) -> Result<u64, Error<R::Error>> {
+    pub(crate) fn read_compact(&mut self) -> Result<u128, Error<R::Error>> {
         let mut head = 0;
-        self.0.read_exact(core::slice::from_mut(&mut head))?;
+        self.read_exact(core::slice::from_mut(&mut head))?;
         match head & 0x3 {
-            0x0 => Ok((head >> 2) as u64),
+            0x0 => Ok((head >> 2) as u128),
             0x1 => {
-                let low = (head >> 2) as u64;
-                let high = self.read_u8()? as u64;
+                let low = (head >> 2) as u128;
+                let high = self.read_u8()? as u128;
                 Ok(low | high << 6)
             }
             0x2 => {
-                let low = (head >> 2) as u64;
+                let low = (head >> 2) as u128;
                 let mut high = [0; 4];
-                self.0.read_exact(&mut high[..3])?;
-                let high = u32::from_le_bytes(high) as u64;
+                self.read_exact(&mut high[..3])?;
+                let high = u32::from_le_bytes(high) as u128;
                 Ok(low | high << 6)
             }
             0x3 => {
                 let len = (head >> 2) as usize + 4;
-                if len > 8 {
+                if len > 16 {
                     return Err(Error::CollectionTooLargeToDeserialize);
                 }
-                let mut buf = [0; 8];
-                self.0.read_exact(&mut buf[..len])?;
-                let n = u64::from_le_bytes(buf);
+                let mut buf = [0; 16];
+                self.read_exact(&mut buf[..len])?;
+                let n = u128::from_le_bytes(buf);
                 Ok(n)
             }
             _ => unreachable!(),
         }
     }
 
+    /// Reads a sequence of fixed-size integers as a single blob of bytes
+    #[cfg(feature = "vec_of_le_ints")]
+    fn deserialize_le_ints<V>(
+        &mut self,
+        size: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error<R::Error>>
+    where
+        V: Visitor<'de>,
+    {
+        let n = self.read_seq_len()?
+            .checked_mul(size)
+            .ok_or(Error::CollectionTooLargeToDeserialize)?;
+        self.read_map(n, |bytes| {
+            match bytes {
+                Bytes::Persistent(b) => visitor.visit_borrowed_bytes(b),
+                Bytes::Temporary(b) => visitor.visit_bytes(b),
+            }
+        })?
+    }
+
+    fn read_seq_len(&mut self) -> Result<usize, Error<R::Error>> {
+        let len = self.read_len()?;
+        match self.limits.max_seq {
+            Some(limit) if len > limit => Err(Error::SequenceLengthExceeded { len, limit }),
+            _ => Ok(len),
+        }
+    }
+
+    fn read_map_len(&mut self) -> Result<usize, Error<R::Error>> {
+        let len = self.read_len()?;
+        match self.limits.max_map {
+            Some(limit) if len > limit => Err(Error::MapLengthExceeded { len, limit }),
+            _ => Ok(len),
+        }
+    }
+
+    fn read_bytes_len(&mut self) -> Result<usize, Error<R::Error>> {
+        let len = self.read_len()?;
+        match self.limits.max_bytes {
+            Some(limit) if len > limit => Err(Error::BytesLengthExceeded { len, limit }),
+            _ => Ok(len),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<usize, Error<R::Error>> {
+        let len = if self.leb128_lengths {
+            u128::from(self.read_leb128()?)
+        } else {
+            self.read_compact()?
+        };
+        let len = usize::try_from(len).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
+        match self.limits.max_len {
+            Some(limit) if len > limit => {
+                return Err(Error::CollectionLengthExceeded { len, limit });
+            }
+            _ => {}
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(validate) = &mut self.length_validator {
+            validate(len as u64).map_err(Error::Other)?;
+        }
+        Ok(len)
+    }
+
+    /// Deserializes `len` consecutive values, named after `fields` in error paths if not empty
+    fn deserialize_fields<V>(
+        &mut self,
+        len: usize,
+        fields: &'static [&'static str],
+        stop_at_end: bool,
+        visitor: V,
+    ) -> Result<V::Value, Error<R::Error>>
+    where
+        V: Visitor<'de>,
+    {
+        self.nested(|deserializer| {
+            visitor.visit_seq(Sequence {
+                deserializer,
+                index: 0,
+                remaining: len,
+                fields,
+                stop_at_end,
+            })
+        })
+    }
+
+    /// Appends `segment` to the error path until the matching call to `leave`
+    fn enter(&mut self, segment: PathSegment) {
+        #[cfg(feature = "alloc")]
+        if let Some(path) = &mut self.path {
+            path.push((segment, self.position));
+        }
+        #[cfg(not(feature = "alloc"))]
+        let _ = segment;
+    }
+
+    /// Removes the last segment of the error path, attaching the path to `res` if it failed
+    ///
+    /// Errors already carrying a path are left unchanged, so the path points to the innermost
+    /// value that failed.
+    fn leave<T>(&mut self, res: Result<T, Error<R::Error>>) -> Result<T, Error<R::Error>> {
+        #[cfg(feature = "alloc")]
+        if let Some(path) = &mut self.path {
+            let res = res.map_err(|e| match e {
+                e @ Error::WithPath { .. } => e,
+                e => Error::WithPath {
+                    path: path.iter().map(|&(segment, _)| segment).collect(),
+                    offset: path.last().map_or(0, |&(_, offset)| offset),
+                    error: Box::new(e),
+                },
+            });
+            path.pop();
+            return res;
+        }
+        res
+    }
+
+    fn nested<T, F>(&mut self, f: F) -> Result<T, Error<R::Error>>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error<R::Error>>,
+    {
+        match self.limits.max_depth {
+            Some(limit) if self.depth >= limit => {
+                return Err(Error::RecursionLimitExceeded { limit });
+            }
+            _ => {}
+        }
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+        res
+    }
+
+    fn read_leb128(&mut self) -> Result<u64, Error<R::Error>> {
+        let mut v = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            let low = (byte & 0x7f) as u64;
+            if (shift == 63 && low > 1) || shift > 63 {
+                return Err(Error::CollectionTooLargeToDeserialize);
+            }
+            v |= low << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+            shift += 7;
+        }
+    }
+
+    /// Deserializes a [`Nested`](crate::Nested) value from the byte array that follows
+    ///
+    /// The value is read in place, so the options and limits of this deserializer apply to it.
+    #[cfg(feature = "alloc")]
+    fn deserialize_nested<V>(&mut self, visitor: V) -> Result<V::Value, Error<R::Error>>
+    where
+        V: Visitor<'de>,
+    {
+        let len = self.read_bytes_len()?;
+        self.consume_nested(len)?;
+        let outer = self.nested_left.replace(len);
+        let res = visitor.visit_newtype_struct(&mut *self);
+        let left = core::mem::replace(&mut self.nested_left, outer);
+        let value = res?;
+        match left {
+            Some(0) | None => Ok(value),
+            Some(remaining) => Err(Error::TrailingBytes { remaining }),
+        }
+    }
+
+    /// Counts `n` bytes as read from the [`Nested`](crate::Nested) value being deserialized,
+    /// failing if they do not fit in it
+    #[cfg(feature = "alloc")]
+    fn consume_nested(&mut self, n: usize) -> Result<(), Error<R::Error>> {
+        if let Some(left) = &mut self.nested_left {
+            *left = left.checked_sub(n).ok_or_else(|| {
+                Error::Other("Nested value extends past the end of its byte array".into())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error<R::Error>> {
+        #[cfg(feature = "alloc")]
+        self.consume_nested(buf.len())?;
+        self.reader.read_exact(buf)?;
+        self.position += buf.len();
+        #[cfg(feature = "alloc")]
+        if let Some(captured) = &mut self.captured {
+            captured.extend_from_slice(buf);
+        }
+        Ok(())
+    }
+
+    fn read_map<T, F>(&mut self, n: usize, f: F) -> Result<T, Error<R::Error>>
+    where
+        F: FnOnce(Bytes<'de, '_>) -> T,
+    {
+        #[cfg(feature = "alloc")]
+        self.consume_nested(n)?;
+        #[cfg(feature = "alloc")]
+        if let Some(captured) = &mut self.captured {
+            let res = self.reader.read_map(n, |bytes| {
+                captured.extend_from_slice(&bytes);
+                f(bytes)
+            })?;
+            self.position += n;
+            return Ok(res);
+        }
+        let res = self.reader.read_map(n, f)?;
+        self.position += n;
+        Ok(res)
+    }
+
+    /// Deserializes a value and returns the bytes it was read from along with it
+    #[cfg(feature = "alloc")]
+    fn capturing<T, F>(&mut self, f: F) -> (Result<T, Error<R::Error>>, Vec<u8>)
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error<R::Error>>,
+    {
+        let outer = self.captured.replace(Vec::new());
+        let res = f(self);
+        let bytes = self.captured.take().unwrap_or_default();
+        self.captured = outer.map(|mut outer| {
+            outer.extend_from_slice(&bytes);
+            outer
+        });
+        (res, bytes)
+    }
+
     fn read_u8(&mut self) -> Result<u8, Error<R::Error>> {
         let mut v = 0;
-        self.0.read_exact(core::slice::from_mut(&mut v))?;
+        self.read_exact(core::slice::from_mut(&mut v))?;
         Ok(v)
     }
 
     fn read_u32(&mut self) -> Result<u32, Error<R::Error>> {
         let mut v = [0; 4];
-        self.0.read_exact(&mut v)?;
+        self.read_exact(&mut v)?;
         Ok(u32::from_le_bytes(v))
     }
 }
 
+impl<R> Debug for Deserializer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Deserializer");
+        s.field("position", &self.position)
+            .field("limits", &self.limits)
+            .field("depth", &self.depth)
+            .field("default_trailing_fields", &self.default_trailing_fields)
+            .field("none_at_end", &self.none_at_end)
+            .field("leb128_lengths", &self.leb128_lengths)
+            .field("unit_placeholder", &self.unit_placeholder);
+        #[cfg(feature = "alloc")]
+        s.field("length_validator", &self.length_validator.is_some())
+            .field("unique_map_keys", &self.unique_map_keys)
+            .field("error_path", &self.path.is_some());
+        s.finish_non_exhaustive()
+    }
+}
+
 impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     type Error = Error<R::Error>;
 
@@ -99,7 +828,7 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_i8(i8::from_le_bytes(found))
     }
 
@@ -108,7 +837,7 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0; 2];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_i16(i16::from_le_bytes(found))
     }
 
@@ -117,7 +846,7 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0; 4];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_i32(i32::from_le_bytes(found))
     }
 
@@ -126,11 +855,20 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0; 8];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_i64(i64::from_le_bytes(found))
 
     }
 
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut found = [0; 16];
+        self.read_exact(&mut found)?;
+        visitor.visit_i128(i128::from_le_bytes(found))
+    }
+
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -143,7 +881,7 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0; 2];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_u16(u16::from_le_bytes(found))
     }
 
@@ -159,10 +897,19 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         let mut found = [0; 8];
-        self.0.read_exact(&mut found)?;
+        self.read_exact(&mut found)?;
         visitor.visit_u64(u64::from_le_bytes(found))
     }
 
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut found = [0; 16];
+        self.read_exact(&mut found)?;
+        visitor.visit_u128(u128::from_le_bytes(found))
+    }
+
     fn deserialize_f32<V>(self, _: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -190,9 +937,8 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        let n = self.read_compact()?;
-        let n = usize::try_from(n).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
-        self.0.read_map(n, |bytes| {
+        let n = self.read_bytes_len()?;
+        self.read_map(n, |bytes| {
             match bytes {
                 Bytes::Persistent(b) => {
                     let s = core::str::from_utf8(b).map_err(Error::InvalidUnicode)?;
@@ -217,9 +963,8 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        let n = self.read_compact()?;
-        let n = usize::try_from(n).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
-        self.0.read_map(n, |bytes| {
+        let n = self.read_bytes_len()?;
+        self.read_map(n, |bytes| {
             match bytes {
                 Bytes::Persistent(b) => visitor.visit_borrowed_bytes(b),
                 Bytes::Temporary(b) => visitor.visit_bytes(b),
@@ -238,6 +983,9 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
+        if self.none_at_end && self.is_empty() {
+            return visitor.visit_none();
+        }
         match self.read_u8()? {
             0 => visitor.visit_none(),
             1 => visitor.visit_some(OptionalBoolDeserializer::discriminant_1(self)),
@@ -250,6 +998,12 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
+        if self.unit_placeholder {
+            match self.read_u8()? {
+                0 => {}
+                found => return Err(Error::InvalidUnitPlaceholder { found }),
+            }
+        }
         visitor.visit_unit()
     }
 
@@ -261,17 +1015,36 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_unit()
+        self.deserialize_unit(visitor)
     }
 
     fn deserialize_newtype_struct<V>(
         self,
-        _: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == crate::compact::NAME {
+            let v = self.read_compact()?;
+            return match u64::try_from(v) {
+                Ok(v) => visitor.visit_u64(v),
+                Err(_) => visitor.visit_u128(v),
+            };
+        }
+        #[cfg(feature = "alloc")]
+        {
+            if name == crate::nested::NAME {
+                return self.deserialize_nested(visitor);
+            }
+        }
+        #[cfg(feature = "vec_of_le_ints")]
+        {
+            if let Some(size) = crate::vec_of_le_ints::element_size(name) {
+                return self.deserialize_le_ints(size, visitor);
+            }
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -279,8 +1052,7 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        let len = self.read_compact()?;
-        let len = usize::try_from(len).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
+        let len = self.read_seq_len()?;
         self.deserialize_tuple(len, visitor)
     }
 
@@ -288,21 +1060,26 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(Sequence {
-            deserializer: self,
-            remaining: len,
-        })
+        self.deserialize_fields(len, &[], false, visitor)
     }
 
     fn deserialize_tuple_struct<V>(
         self,
-        _: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == crate::raw_bytes::NAME {
+            return self.read_map(len, |bytes| {
+                match bytes {
+                    Bytes::Persistent(b) => visitor.visit_borrowed_bytes(b),
+                    Bytes::Temporary(b) => visitor.visit_bytes(b),
+                }
+            })?;
+        }
         self.deserialize_tuple(len, visitor)
     }
 
@@ -310,12 +1087,8 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        let len = self.read_compact()?;
-        let len = usize::try_from(len).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
-        visitor.visit_map(Map {
-            deserializer: self,
-            remaining: len,
-        })
+        let len = self.read_map_len()?;
+        self.nested(|deserializer| visitor.visit_map(Map::new(deserializer, len)))
     }
 
     fn deserialize_struct<V>(
@@ -327,20 +1100,25 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        self.deserialize_fields(fields.len(), fields, false, visitor)
     }
 
     fn deserialize_enum<V>(
         self,
-        _: &'static str,
-        _: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(Enum {
-            deserializer: self,
+        self.nested(|deserializer| {
+            visitor.visit_enum(Enum {
+                deserializer,
+                name,
+                variants,
+                variant: "",
+            })
         })
     }
 
@@ -357,11 +1135,18 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for &mut Deserializer<R> {
     {
         self.deserialize_any(visitor)
     }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 }
 
 struct Sequence<'a, R> {
     deserializer: &'a mut Deserializer<R>,
+    index: usize,
     remaining: usize,
+    fields: &'static [&'static str],
+    stop_at_end: bool,
 }
 
 impl<'a, 'de, R: Read<'de>> serde::de::SeqAccess<'de> for Sequence<'a, R> {
@@ -374,18 +1159,87 @@ impl<'a, 'de, R: Read<'de>> serde::de::SeqAccess<'de> for Sequence<'a, R> {
         if self.remaining == 0 {
             return Ok(None);
         }
+        if self.stop_at_end && self.deserializer.is_empty() {
+            self.remaining = 0;
+            return Ok(None);
+        }
         self.remaining -= 1;
-        seed.deserialize(&mut *self.deserializer).map(Some)
+        let segment = match self.fields.get(self.index) {
+            Some(&name) => PathSegment::Field(name),
+            None => PathSegment::Index(self.index),
+        };
+        self.index += 1;
+        self.deserializer.enter(segment);
+        let res = seed.deserialize(&mut *self.deserializer);
+        self.deserializer.leave(res).map(Some)
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.remaining)
+        Some(capped_size_hint(self.remaining, &self.deserializer.reader))
     }
 }
 
-struct Map<'a, R> {
+/// Caps the number of elements a collection should preallocate to the number of bytes left
+///
+/// Most elements take at least one byte, so this prevents a hostile length from causing a large
+/// allocation when the input is small.
+fn capped_size_hint<'de, R: Read<'de>>(len: usize, reader: &R) -> usize {
+    reader.remaining().map_or(len, |bytes| len.min(bytes))
+}
+
+/// Access to the entries of a map, for driving map deserialization manually
+///
+/// This implements [`serde::de::MapAccess`], whose methods pull keys and values in turn. It is
+/// returned by [`Deserializer::map`].
+pub struct Map<'a, R> {
     deserializer: &'a mut Deserializer<R>,
+    index: usize,
     remaining: usize,
+    #[cfg(feature = "alloc")]
+    seen_keys: Option<BTreeSet<Vec<u8>>>,
+}
+
+impl<'a, R> Map<'a, R> {
+    fn new(deserializer: &'a mut Deserializer<R>, remaining: usize) -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            seen_keys: if deserializer.unique_map_keys { Some(BTreeSet::new()) } else { None },
+            deserializer,
+            index: 0,
+            remaining,
+        }
+    }
+
+    /// Returns the number of entries left
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, 'de, R: Read<'de>> Map<'a, R> {
+    fn read_key<K>(&mut self, seed: K) -> Result<K::Value, Error<R::Error>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        #[cfg(feature = "alloc")]
+        if let Some(seen_keys) = &mut self.seen_keys {
+            let (key, bytes) = self.deserializer.capturing(|d| seed.deserialize(d));
+            let key = key?;
+            if !seen_keys.insert(bytes) {
+                return Err(Error::DuplicateMapKey { index: seen_keys.len() });
+            }
+            return Ok(key);
+        }
+        seed.deserialize(&mut *self.deserializer)
+    }
+}
+
+impl<R> Debug for Map<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a, 'de, R: Read<'de>> serde::de::MapAccess<'de> for Map<'a, R> {
@@ -399,19 +1253,32 @@ impl<'a, 'de, R: Read<'de>> serde::de::MapAccess<'de> for Map<'a, R> {
             return Ok(None);
         }
         self.remaining -= 1;
-        seed.deserialize(&mut *self.deserializer).map(Some)
+        self.index += 1;
+        self.deserializer.enter(PathSegment::Index(self.index - 1));
+        let res = self.read_key(seed);
+        self.deserializer.leave(res).map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
         V: DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.deserializer)
+        self.deserializer.enter(PathSegment::Index(self.index.saturating_sub(1)));
+        let res = seed.deserialize(&mut *self.deserializer);
+        self.deserializer.leave(res)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(capped_size_hint(self.remaining, &self.deserializer.reader))
     }
 }
 
 struct Enum<'a, R> {
     deserializer: &'a mut Deserializer<R>,
+    name: &'static str,
+    variants: &'static [&'static str],
+    /// Name of the variant read, once known
+    variant: &'static str,
 }
 
 impl<'a, 'de, R: Read<'de>> serde::de::EnumAccess<'de> for Enum<'a, R> {
@@ -422,7 +1289,16 @@ impl<'a, 'de, R: Read<'de>> serde::de::EnumAccess<'de> for Enum<'a, R> {
     where
         V: DeserializeSeed<'de>,
     {
-        Ok((seed.deserialize(&mut *self.deserializer)?, self))
+        let found_discriminant = self.deserializer.read_u8()?;
+        let discriminant: U8Deserializer<Self::Error> = found_discriminant.into_deserializer();
+        // The seed decides which discriminants are valid, e.g. `#[serde(other)]` accepts any.
+        let value = seed.deserialize(discriminant).map_err(|_| Error::UnknownVariant {
+            enum_name: self.name,
+            found_discriminant,
+        })?;
+        let variant = self.variants.get(usize::from(found_discriminant)).copied();
+        let variant = variant.unwrap_or_default();
+        Ok((value, Self { variant, ..self }))
     }
 }
 
@@ -437,25 +1313,33 @@ impl<'a, 'de, R: Read<'de>> serde::de::VariantAccess<'de> for Enum<'a, R> {
     where
         T: DeserializeSeed<'de>,
     {
-        seed.deserialize(self.deserializer)
+        self.deserializer.enter(PathSegment::Variant(self.variant));
+        let res = seed.deserialize(&mut *self.deserializer);
+        self.deserializer.leave(res)
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserializer.deserialize_tuple(len, visitor)
+        let stop_at_end = self.deserializer.default_trailing_fields;
+        self.deserializer.enter(PathSegment::Variant(self.variant));
+        let res = self.deserializer.deserialize_fields(len, &[], stop_at_end, visitor);
+        self.deserializer.leave(res)
     }
 
     fn struct_variant<V>(
         self,
-        _: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserializer.deserialize_seq(visitor)
+        let stop_at_end = self.deserializer.default_trailing_fields;
+        self.deserializer.enter(PathSegment::Variant(self.variant));
+        let res = self.deserializer.deserialize_fields(fields.len(), fields, stop_at_end, visitor);
+        self.deserializer.leave(res)
     }
 }
 
@@ -538,6 +1422,14 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for OptionalBoolDeserializer<'_
         self.inner.deserialize_i64(visitor)
     }
 
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_bad_discriminant()?;
+        self.inner.deserialize_i128(visitor)
+    }
+
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -570,6 +1462,14 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for OptionalBoolDeserializer<'_
         self.inner.deserialize_u64(visitor)
     }
 
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_bad_discriminant()?;
+        self.inner.deserialize_u128(visitor)
+    }
+
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -744,11 +1644,19 @@ impl<'de, R: Read<'de>> serde::Deserializer<'de> for OptionalBoolDeserializer<'_
         self.check_bad_discriminant()?;
         self.inner.deserialize_ignored_any(visitor)
     }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::from_slice;
+    use crate::{
+        from_slice, from_slice_advancing, from_slice_strict, Compact, Deserializer, EndOfInput,
+        Error,
+    };
+    use serde::Deserialize;
 
     #[test]
     fn none_bool_deserializes_from_0() {
@@ -764,4 +1672,244 @@ mod tests {
     fn some_false_deserializes_from_2() {
         assert_eq!(from_slice::<Option<bool>>(&[2]).unwrap(), Some(false));
     }
+
+    #[test]
+    fn invalid_option_bool_discriminant_is_reported() {
+        for found in [3, 255] {
+            assert!(matches!(
+                from_slice::<Option<bool>>(&[found]),
+                Err(Error::InvalidOption { found_discriminant }) if found_discriminant == found,
+            ));
+        }
+        assert!(matches!(
+            from_slice::<(u8, Option<bool>)>(&[0, 3]),
+            Err(Error::InvalidOption { found_discriminant: 3 }),
+        ));
+    }
+
+    #[test]
+    fn strict_deserialization_requires_exact_consumption() {
+        assert_eq!(from_slice_strict::<(u8, bool)>(&[7, 1]).unwrap(), (7, true));
+        assert!(matches!(
+            from_slice_strict::<(u8, bool)>(&[7, 1, 0]),
+            Err(Error::TrailingBytes { remaining: 1 }),
+        ));
+        assert!(matches!(from_slice_strict::<(u8, bool)>(&[7]), Err(Error::Io(EndOfInput))));
+        assert_eq!(from_slice::<(u8, bool)>(&[7, 1, 0]).unwrap(), (7, true));
+    }
+
+    #[test]
+    fn advancing_deserialization_reads_consecutive_values() {
+        let bytes = [7, 0x0c, b'a', b'b', b'c', 9];
+        let mut input = &bytes[..];
+        assert_eq!(from_slice_advancing::<u8>(&mut input).unwrap(), 7);
+        assert_eq!(from_slice_advancing::<&str>(&mut input).unwrap(), "abc");
+        assert_eq!(input, [9]);
+        assert!(matches!(from_slice_advancing::<u16>(&mut input), Err(Error::Io(EndOfInput))));
+        assert_eq!(input, [9]);
+    }
+
+    #[test]
+    fn values_are_read_until_input_is_empty() {
+        let bytes = [1, 0, 2, 0, 3, 0];
+        let mut deserializer = Deserializer::new(&bytes[..]);
+        let mut values = [0u16; 3];
+        let mut count = 0;
+        while !deserializer.is_empty() {
+            values[count] = u16::deserialize(&mut deserializer).unwrap();
+            count += 1;
+        }
+        assert_eq!(values, [1, 2, 3]);
+        assert_eq!(deserializer.remaining(), Some(0));
+    }
+
+    #[test]
+    fn position_points_past_malformed_boolean() {
+        let bytes = [0x08, 1, 0, 1, 1, 0, 1, 3, 1];
+        let mut deserializer = Deserializer::new(&bytes[..]);
+        let res = <(Compact<u32>, [bool; 7])>::deserialize(&mut deserializer);
+        assert!(matches!(res, Err(Error::ExpectedBoolean { found: 3 })));
+        assert_eq!(deserializer.position(), 8);
+        assert_eq!(bytes[deserializer.position() - 1], 3);
+    }
+
+    #[test]
+    fn position_counts_borrowed_and_copied_bytes() {
+        let bytes = [0x0c, b'a', b'b', b'c', 1, 0];
+        let mut deserializer = Deserializer::new(&bytes[..]);
+        let value = <(&str, u16)>::deserialize(&mut deserializer).unwrap();
+        assert_eq!(value, ("abc", 1));
+        assert_eq!(deserializer.position(), bytes.len());
+        assert!(u8::deserialize(&mut deserializer).is_err());
+        assert_eq!(deserializer.position(), bytes.len());
+    }
+
+    #[test]
+    fn known_variants_deserialize() {
+        assert_eq!(from_slice::<Result<u8, u8>>(&[0, 5]).unwrap(), Ok(5));
+        assert_eq!(from_slice::<Result<u8, u8>>(&[1, 5]).unwrap(), Err(5));
+    }
+
+    #[test]
+    fn struct_variant_fields_have_no_length_prefix() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        enum Shape {
+            Rect { w: u8, h: u8 },
+        }
+
+        let res = from_slice::<(Shape, u8)>(&[0, 2, 3, 9]).unwrap();
+        assert_eq!(res, (Shape::Rect { w: 2, h: 3 }, 9));
+    }
+
+    #[test]
+    fn out_of_range_discriminant_is_unknown_variant() {
+        let res = from_slice::<Result<u8, u8>>(&[2, 5]);
+        assert!(matches!(
+            res,
+            Err(Error::UnknownVariant { enum_name: "Result", found_discriminant: 2 }),
+        ));
+    }
+
+    #[test]
+    fn out_of_range_discriminant_falls_back_to_other_variant() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        enum Kind {
+            A,
+            B,
+            #[serde(other)]
+            Unknown,
+        }
+
+        assert_eq!(from_slice::<Kind>(&[1]).unwrap(), Kind::B);
+        assert_eq!(from_slice::<Kind>(&[7]).unwrap(), Kind::Unknown);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn debug_shows_limits() {
+        use crate::{DeLimits, Deserializer};
+
+        let limits = DeLimits { max_map: Some(7), ..DeLimits::default() };
+        let deserializer = Deserializer::new(&[][..]).with_limits(limits);
+        let debug = alloc::format!("{:?}", deserializer);
+        assert!(debug.contains("max_map: Some(7)"), "{}", debug);
+    }
+
+    #[test]
+    fn read_array_reads_same_bytes_as_array() {
+        let bytes = [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 9];
+        let mut deserializer = Deserializer::new(&bytes[..]);
+        let values = deserializer.read_array::<u32, 4>().unwrap();
+        assert_eq!(values, [1, 2, 3, 4]);
+        assert_eq!(values, from_slice::<[u32; 4]>(&bytes).unwrap());
+        assert_eq!(deserializer.into_inner(), [9]);
+    }
+
+    #[test]
+    fn read_array_supports_long_arrays() {
+        let bytes = [7; 40];
+        let values = Deserializer::new(&bytes[..]).read_array::<u8, 40>().unwrap();
+        assert_eq!(values, [7; 40]);
+    }
+
+    #[test]
+    fn read_array_fails_on_short_input() {
+        let res = Deserializer::new(&[1, 0, 2][..]).read_array::<u16, 2>();
+        assert!(matches!(res, Err(Error::Io(EndOfInput))));
+    }
+
+    #[test]
+    fn size_hint_is_capped_by_remaining_input() {
+        use core::fmt;
+        use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+
+        struct SizeHint(Option<usize>);
+
+        impl<'de> Deserialize<'de> for SizeHint {
+            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                struct V;
+
+                impl<'de> Visitor<'de> for V {
+                    type Value = SizeHint;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("a sequence")
+                    }
+
+                    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<SizeHint, A::Error> {
+                        Ok(SizeHint(seq.size_hint()))
+                    }
+                }
+
+                d.deserialize_seq(V)
+            }
+        }
+
+        let hostile = [0xfe, 0xff, 0xff, 0xff, 1, 2, 3];
+        assert_eq!(from_slice::<SizeHint>(&hostile).unwrap().0, Some(3));
+        let legitimate = [12, 1, 2, 3, 4];
+        assert_eq!(from_slice::<SizeHint>(&legitimate).unwrap().0, Some(3));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn hostile_length_fails_without_large_allocation() {
+        use alloc::vec::Vec;
+
+        let hostile = [0xfe, 0xff, 0xff, 0xff, 1, 2, 3];
+        assert!(matches!(from_slice::<Vec<u64>>(&hostile), Err(Error::Io(EndOfInput))));
+    }
+
+    #[test]
+    fn compact_values_wider_than_64_bits_are_decoded() {
+        let mut two_pow_64 = [0; 10];
+        two_pow_64[0] = 0x17;
+        two_pow_64[9] = 1;
+        let v = Deserializer::new(&two_pow_64[..]).read_compact().unwrap();
+        assert_eq!(v, 1 << 64);
+        let mut two_pow_100 = [0; 14];
+        two_pow_100[0] = 0x27;
+        two_pow_100[13] = 0x10;
+        let v = Deserializer::new(&two_pow_100[..]).read_compact().unwrap();
+        assert_eq!(v, 1 << 100);
+        let mut max = [0xff; 17];
+        max[0] = 0x33;
+        let v = Deserializer::new(&max[..]).read_compact().unwrap();
+        assert_eq!(v, u128::MAX);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn compact_value_is_read_in_at_most_two_calls() {
+        use crate::testing::RecordingReader;
+
+        let cases: [(&[u8], &[usize], u128); 4] = [
+            (&[0xfc], &[1], 63),
+            (&[0x01, 0x01], &[1, 1], 64),
+            (&[0x02, 0x00, 0x01, 0x00], &[1, 3], 0x4000),
+            (&[0x13, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], &[1, 8], u64::MAX.into()),
+        ];
+        for (input, reads, expected) in cases {
+            let mut deserializer = Deserializer::new(RecordingReader::new(input));
+            assert_eq!(deserializer.read_compact().unwrap(), expected);
+            assert_eq!(deserializer.into_inner().reads, reads);
+        }
+    }
+
+    #[test]
+    fn compact_values_wider_than_128_bits_are_rejected() {
+        let mut input = [0; 18];
+        input[0] = 0x37;
+        let res = Deserializer::new(&input[..]).read_compact();
+        assert!(matches!(res, Err(Error::CollectionTooLargeToDeserialize)));
+    }
+
+    #[test]
+    fn lengths_wider_than_usize_are_rejected() {
+        let mut input = [0; 17];
+        input[0] = 0x33;
+        input[16] = 1;
+        let res = from_slice::<&[u8]>(&input);
+        assert!(matches!(res, Err(Error::CollectionTooLargeToDeserialize)));
+    }
 }
@@ -0,0 +1,49 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `Duration` as seconds and nanoseconds
+//!
+//! A duration is encoded as its whole seconds as a `u64` followed by its sub-second nanoseconds
+//! as a `u32`, i.e. 12 bytes. Deserialization fails if the nanoseconds are not below one billion.
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")] {
+//! use core::time::Duration;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Timeout {
+//!     #[serde(with = "serde_scale::duration")]
+//!     after: Duration,
+//! }
+//!
+//! let timeout = Timeout { after: Duration::new(3, 500) };
+//! let bytes = serde_scale::to_vec(&timeout).unwrap();
+//! assert_eq!(bytes, [3, 0, 0, 0, 0, 0, 0, 0, 0xf4, 1, 0, 0]);
+//! assert_eq!(serde_scale::from_slice::<Timeout>(&bytes).unwrap(), timeout);
+//! # }
+//! ```
+
+use core::time::Duration;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Serializes a duration as whole seconds (`u64`) followed by nanoseconds (`u32`)
+pub fn serialize<S>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    (d.as_secs(), d.subsec_nanos()).serialize(serializer)
+}
+
+/// Deserializes a duration from whole seconds (`u64`) followed by nanoseconds (`u32`)
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let (secs, nanos) = <(u64, u32)>::deserialize(deserializer)?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(D::Error::custom("Duration nanoseconds must be less than one billion"));
+    }
+    Ok(Duration::new(secs, nanos))
+}
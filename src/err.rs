@@ -1,11 +1,20 @@
 // Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
 
 #[cfg(feature = "alloc")]
-use alloc::string::ToString;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::fmt::{self, Debug, Display};
 
 /// Serialization errors
+///
+/// Variants may be added in future versions and some only exist with the `alloc` feature, so
+/// matching on this type requires a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error<E> {
     /// SCALE does not specify how to serialize floating point values
     FloatingPointUnsupported,
@@ -33,16 +42,94 @@ pub enum Error<E> {
     },
     /// This implementation limits collections to 2^64 elements
     CollectionTooLargeToDeserialize,
+    /// A sequence is longer than the configured limit
+    SequenceLengthExceeded {
+        len: usize,
+        limit: usize,
+    },
+    /// A map has more entries than the configured limit
+    MapLengthExceeded {
+        len: usize,
+        limit: usize,
+    },
+    /// A string or byte array is longer than the configured limit
+    BytesLengthExceeded {
+        len: usize,
+        limit: usize,
+    },
+    /// A collection is longer than the limit configured for all collections
+    CollectionLengthExceeded {
+        len: usize,
+        limit: usize,
+    },
+    /// Values are nested deeper than the configured limit
+    RecursionLimitExceeded {
+        limit: usize,
+    },
     /// Invalid Unicode was found in a string
     InvalidUnicode(core::str::Utf8Error),
+    /// The discriminant does not match any variant of the enumeration
+    UnknownVariant {
+        enum_name: &'static str,
+        found_discriminant: u8,
+    },
+    /// The version preceding a value is not supported
+    UnknownVersion {
+        found: u8,
+    },
+    /// Bytes were left after deserializing a value
+    TrailingBytes {
+        remaining: usize,
+    },
     /// An option was expected but the discriminant is invalid
     InvalidOption {
         found_discriminant: u8,
     },
+    /// The entry at `index` of a map repeats the key of an earlier entry
+    DuplicateMapKey {
+        index: usize,
+    },
+    /// A unit placeholder (0) was expected but another byte was found
+    InvalidUnitPlaceholder {
+        found: u8,
+    },
     /// I/O error from the underlying reader or writer
     Io(E),
     /// Other error the serializer or deserializer might encounter
     Other(OtherError),
+    /// Error found at `path` within the value being deserialized
+    ///
+    /// `offset` is the position in the input of the first byte of the value that failed.
+    ///
+    /// This is only returned by deserializers created with
+    /// [`with_error_path`](crate::Deserializer::with_error_path).
+    #[cfg(feature = "alloc")]
+    WithPath {
+        path: Vec<PathSegment>,
+        offset: usize,
+        error: Box<Error<E>>,
+    },
+}
+
+/// Step from a value to one of its parts, as listed in [`Error::WithPath`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathSegment {
+    /// Named field of a structure or struct variant
+    Field(&'static str),
+    /// Element of a sequence, tuple or tuple variant, or entry of a map
+    Index(usize),
+    /// Content of an enumeration variant
+    Variant(&'static str),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::Index(i) => write!(f, "[{}]", i),
+            PathSegment::Variant(name) => write!(f, "::{}", name),
+        }
+    }
 }
 
 impl<E> From<E> for Error<E> {
@@ -80,21 +167,118 @@ impl<E: Display> Display for Error<E> {
             Error::CollectionTooLargeToDeserialize => {
                 write!(f, "Collections of more than 2^64 elements are not supported")
             }
+            Error::SequenceLengthExceeded { len, limit } => {
+                write!(f, "Found a sequence of {} elements but the limit is {}", len, limit)
+            }
+            Error::MapLengthExceeded { len, limit } => {
+                write!(f, "Found a map of {} entries but the limit is {}", len, limit)
+            }
+            Error::BytesLengthExceeded { len, limit } => {
+                write!(f, "Found {} bytes but the limit is {}", len, limit)
+            }
+            Error::CollectionLengthExceeded { len, limit } => {
+                write!(f, "Found a collection of length {} but the limit is {}", len, limit)
+            }
+            Error::RecursionLimitExceeded { limit } => {
+                write!(f, "Values are nested more than {} levels deep", limit)
+            }
             Error::InvalidUnicode(e) => {
                 write!(f, "Invalid Unicode in string: {}", e)
             }
+            Error::UnknownVariant { enum_name, found_discriminant } => {
+                write!(f, "Discriminant {} does not match any variant of {}", found_discriminant,
+                    enum_name)
+            }
+            Error::UnknownVersion { found } => {
+                write!(f, "Unsupported version {}", found)
+            }
+            Error::TrailingBytes { remaining } => {
+                write!(f, "{} bytes were left after deserializing the value", remaining)
+            }
             Error::InvalidOption { found_discriminant } => {
                 write!(f, "Invalid option. Expected a discriminant of 0 or 1 but found {}",
                     found_discriminant)
             }
+            Error::DuplicateMapKey { index } => {
+                write!(f, "Map entry {} repeats the key of an earlier entry", index)
+            }
+            Error::InvalidUnitPlaceholder { found } => {
+                write!(f, "Expected unit placeholder (0), found {}", found)
+            }
             Error::Io(e) => {
                 write!(f, "I/O error: {}", e)
             }
             Error::Other(e) => write!(f, "{}", e),
+            #[cfg(feature = "alloc")]
+            Error::WithPath { path, error, .. } => {
+                write!(f, "{} at ", error)?;
+                path.iter().try_for_each(|segment| write!(f, "{}", segment))
+            }
         }
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<E: Display> Error<E> {
+    /// Returns a multi-line diagnostic made of the error message followed by its context
+    pub fn report(&self) -> String {
+        use core::fmt::Write as _;
+
+        if let Error::WithPath { path, offset, error } = self {
+            let mut out = error.report();
+            let _ = write!(out, "\n  offset: {}\n  path: ", offset);
+            path.iter().for_each(|segment| {
+                let _ = write!(out, "{}", segment);
+            });
+            return out;
+        }
+        let mut out = format!("error: {}", self);
+        let mut context = |name: &str, value: &dyn Display| {
+            let _ = write!(out, "\n  {}: {}", name, value);
+        };
+        match self {
+            Error::TooManyVariants { enum_name, variant_name, variant_index } => {
+                context("enum", enum_name);
+                context("variant", variant_name);
+                context("variant index", variant_index);
+            }
+            Error::UnknownVariant { enum_name, found_discriminant } => {
+                context("enum", enum_name);
+                context("discriminant", found_discriminant);
+            }
+            Error::InvalidOption { found_discriminant } => {
+                context("discriminant", found_discriminant);
+            }
+            Error::ExpectedBoolean { found }
+            | Error::UnknownVersion { found }
+            | Error::InvalidUnitPlaceholder { found } => {
+                context("found", found);
+            }
+            Error::InvalidCharacter { found } => context("found", found),
+            Error::SequenceLengthExceeded { len, limit }
+            | Error::MapLengthExceeded { len, limit }
+            | Error::BytesLengthExceeded { len, limit }
+            | Error::CollectionLengthExceeded { len, limit } => {
+                context("length", len);
+                context("limit", limit);
+            }
+            Error::RecursionLimitExceeded { limit } => context("limit", limit),
+            Error::DuplicateMapKey { index } => context("entry", index),
+            Error::TrailingBytes { remaining } => context("remaining", remaining),
+            Error::CollectionTooLargeToSerialize { len } => context("length", len),
+            Error::FloatingPointUnsupported
+            | Error::LengthNeeded
+            | Error::TypeMustBeKnown
+            | Error::CollectionTooLargeToDeserialize
+            | Error::InvalidUnicode(_)
+            | Error::Io(_)
+            | Error::Other(_)
+            | Error::WithPath { .. } => {}
+        }
+        out
+    }
+}
+
 #[cfg(feature = "std")]
 impl<E: Debug + Display> std::error::Error for Error<E> {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -115,8 +299,19 @@ impl<E: Debug + Display> std::error::Error for Error<E> {
             | Error::InvalidCharacter { .. }
             | Error::CollectionTooLargeToSerialize { .. }
             | Error::CollectionTooLargeToDeserialize
+            | Error::SequenceLengthExceeded { .. }
+            | Error::MapLengthExceeded { .. }
+            | Error::BytesLengthExceeded { .. }
+            | Error::CollectionLengthExceeded { .. }
+            | Error::RecursionLimitExceeded { .. }
+            | Error::UnknownVariant { .. }
+            | Error::UnknownVersion { .. }
+            | Error::TrailingBytes { .. }
             | Error::InvalidOption { .. }
-            | Error::Other(_) => None,
+            | Error::DuplicateMapKey { .. }
+            | Error::InvalidUnitPlaceholder { .. }
+            | Error::Other(_)
+            | Error::WithPath { .. } => None,
         }
     }
 }
@@ -195,3 +390,31 @@ impl Display for OtherError {
         f.write_str(self.as_str())
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{from_slice, Deserializer};
+    use serde::Deserialize;
+
+    #[test]
+    fn report_includes_enum_context() {
+        let report = from_slice::<Result<u8, u8>>(&[7, 0]).unwrap_err().report();
+        assert_eq!(
+            report,
+            "error: Discriminant 7 does not match any variant of Result\n  enum: Result\n  \
+                discriminant: 7",
+        );
+    }
+
+    #[test]
+    fn report_includes_offset_and_path() {
+        let bytes = [1, 2, 0, 7, 0];
+        let mut deserializer = Deserializer::new(&bytes[..]).with_error_path();
+        let e = <(u16, bool, Result<u8, u8>)>::deserialize(&mut deserializer).unwrap_err();
+        assert_eq!(
+            e.report(),
+            "error: Discriminant 7 does not match any variant of Result\n  enum: Result\n  \
+                discriminant: 7\n  offset: 3\n  path: [2]",
+        );
+    }
+}
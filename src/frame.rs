@@ -0,0 +1,107 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Compact, Deserializer, EndOfInput, Error, Serializer, Write};
+use serde::Serialize;
+use std::{convert::TryFrom, io};
+
+/// Reads frames made of a compact length followed by that many bytes from a byte stream
+///
+/// Bytes are buffered until a complete frame is available, so frames may arrive split across
+/// several reads. Each frame can then be deserialized with [`from_slice`](crate::from_slice).
+///
+/// If reading fails (e.g. with [`io::ErrorKind::WouldBlock`] on a non-blocking stream), the
+/// bytes already received are kept and the next call to
+/// [`next_frame`](FrameReader::next_frame) resumes where the previous one stopped.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+}
+
+impl<R: io::Read> FrameReader<R> {
+    /// Returns a frame reader reading from the given stream
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns the next frame or `None` if the stream ended
+    ///
+    /// An error of kind [`io::ErrorKind::UnexpectedEof`] is returned if the stream ends in the
+    /// middle of a frame.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, Error<io::Error>> {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(Some(frame));
+            }
+            let mut chunk = [0; 4096];
+            let n = match self.inner.read(&mut chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::Io(e)),
+            };
+            if n == 0 {
+                return if self.buf.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(Error::Io(io::ErrorKind::UnexpectedEof.into()))
+                };
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Returns the underlying stream, discarding any buffered bytes
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn take_frame(&mut self) -> Result<Option<Vec<u8>>, Error<io::Error>> {
+        let mut deserializer = Deserializer::new(&self.buf[..]);
+        let len = match deserializer.read_compact() {
+            Ok(len) => len,
+            Err(Error::Io(EndOfInput)) => return Ok(None),
+            Err(_) => return Err(Error::CollectionTooLargeToDeserialize),
+        };
+        let len = usize::try_from(len).map_err(|_| Error::CollectionTooLargeToDeserialize)?;
+        let remaining = deserializer.into_inner();
+        if remaining.len() < len {
+            return Ok(None);
+        }
+        let header_len = self.buf.len() - remaining.len();
+        let frame = remaining[..len].to_vec();
+        self.buf.drain(..header_len + len);
+        Ok(Some(frame))
+    }
+}
+
+/// Writes frames made of a compact length followed by that many bytes
+///
+/// This produces the frames read by [`FrameReader`].
+#[derive(Debug)]
+pub struct FramingWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> FramingWriter<W> {
+    /// Returns a frame writer writing to the given writer
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Writes `bytes` preceded by their length
+    pub fn write_frame(&mut self, bytes: &[u8]) -> Result<(), Error<W::Error>> {
+        let len = bytes.len();
+        let len = u64::try_from(len).map_err(|_| Error::CollectionTooLargeToSerialize { len })?;
+        Compact(len).serialize(&mut Serializer::new(&mut self.inner))?;
+        self.inner.write(bytes)?;
+        Ok(())
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Bytes, Error, Read};
+use core::fmt::{self, Debug, Display};
+
+/// Reader limited to a frame of known length
+///
+/// Reading past the end of the frame fails with [`FrameError::EndOfFrame`]. Once a value has been
+/// deserialized, [`finish`](FramedReader::finish) checks or skips the bytes left in the frame.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use serde_scale::{Deserializer, FramedReader};
+///
+/// let input = [1, 0, 2, 3];
+/// let mut deserializer = Deserializer::new(FramedReader::new(&input[..], 3));
+/// let x = u16::deserialize(&mut deserializer).unwrap();
+/// assert_eq!(x, 1);
+/// let rest = deserializer.into_inner().finish().unwrap();
+/// assert_eq!(rest, [3]);
+/// ```
+#[derive(Debug)]
+pub struct FramedReader<R> {
+    inner: R,
+    remaining: usize,
+    exact: bool,
+}
+
+impl<'a, R: Read<'a>> FramedReader<R> {
+    /// Returns a reader limited to the next `len` bytes of `inner`
+    pub fn new(inner: R, len: usize) -> Self {
+        Self {
+            inner,
+            remaining: len,
+            exact: false,
+        }
+    }
+
+    /// Returns a reader requiring the whole frame to be consumed
+    ///
+    /// [`finish`](FramedReader::finish) then fails if bytes are left in the frame.
+    pub fn exact(self) -> Self {
+        Self { exact: true, ..self }
+    }
+
+    /// Returns the number of bytes left in the frame
+    pub fn into_remaining(self) -> usize {
+        self.remaining
+    }
+
+    /// Ends the frame and returns the underlying reader positioned after it
+    ///
+    /// Bytes left in the frame are skipped, unless the reader is [`exact`](FramedReader::exact)
+    /// in which case [`Error::TrailingBytes`] is returned.
+    pub fn finish(mut self) -> Result<R, Error<FrameError<R::Error>>> {
+        if self.remaining != 0 {
+            if self.exact {
+                return Err(Error::TrailingBytes { remaining: self.remaining });
+            }
+            self.inner.read_map(self.remaining, |_| ()).map_err(FrameError::Inner)?;
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<'a, R: Read<'a>> Read<'a> for FramedReader<R> {
+    type Error = FrameError<R::Error>;
+
+    fn read_map<T, F>(&mut self, n: usize, f: F) -> Result<T, Self::Error>
+    where
+        F: FnOnce(Bytes<'a, '_>) -> T,
+    {
+        if n > self.remaining {
+            return Err(FrameError::EndOfFrame);
+        }
+        let res = self.inner.read_map(n, f).map_err(FrameError::Inner)?;
+        self.remaining -= n;
+        Ok(res)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Error reading from a [`FramedReader`]
+#[derive(Debug)]
+pub enum FrameError<E> {
+    /// Attempted to read past the end of the frame
+    EndOfFrame,
+    /// Error from the underlying reader
+    Inner(E),
+}
+
+impl<E: Display> Display for FrameError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::EndOfFrame => f.write_str("Attempted to read past the end of the frame"),
+            FrameError::Inner(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Debug + Display> std::error::Error for FrameError<E> {}
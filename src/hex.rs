@@ -0,0 +1,58 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Error, Serializer, Write};
+use alloc::string::String;
+use core::convert::Infallible;
+use serde::Serialize;
+
+/// Serializes a value using the SCALE encoding and appends it to a string as lowercase hexadecimal
+///
+/// The string is not cleared beforehand, so it can be reused to accumulate several values without
+/// allocating a new string for each.
+pub fn to_hex_into<T>(x: &T, out: &mut String) -> Result<(), Error<Infallible>>
+where
+    T: Serialize + ?Sized,
+{
+    x.serialize(&mut Serializer::new(HexWriter::new(out)))
+}
+
+/// Writer appending bytes to a string as lowercase hexadecimal
+#[derive(Debug)]
+pub struct HexWriter<'a> {
+    out: &'a mut String,
+}
+
+impl<'a> HexWriter<'a> {
+    /// Returns a writer appending to the given string
+    pub fn new(out: &'a mut String) -> Self {
+        Self { out }
+    }
+}
+
+impl Write for HexWriter<'_> {
+    type Error = Infallible;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        self.out.reserve(data.len() * 2);
+        for &b in data {
+            self.out.push(DIGITS[usize::from(b >> 4)] as char);
+            self.out.push(DIGITS[usize::from(b & 0xf)] as char);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::to_hex_into;
+    use alloc::string::String;
+
+    #[test]
+    fn hex_is_appended() {
+        let mut out = String::from("0x");
+        to_hex_into(&0x0102_abcd_u32, &mut out).unwrap();
+        to_hex_into(&Some(0xffu8), &mut out).unwrap();
+        assert_eq!(out, "0xcdab020101ff");
+    }
+}
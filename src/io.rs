@@ -0,0 +1,94 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Bytes, Read, Write};
+use std::io::{self, Read as _};
+
+/// Adapter to use a [`std::io::Write`] as a [`Write`]
+///
+/// All bytes passed to [`Write::write`] are written with [`io::Write::write_all`], so writers
+/// accepting only part of the bytes at a time are supported. A writer making no progress fails
+/// with an error of kind [`io::ErrorKind::WriteZero`], which serialization reports as
+/// [`Error::Io`](crate::Error::Io).
+///
+/// ```rust
+/// use serde_scale::IoWriter;
+/// use std::io::BufWriter;
+///
+/// let mut out = BufWriter::new(Vec::new());
+/// serde_scale::to_writer(IoWriter(&mut out), &(1u8, "one")).unwrap();
+/// assert_eq!(out.into_inner().unwrap(), [1, 12, b'o', b'n', b'e']);
+/// ```
+#[derive(Debug)]
+pub struct IoWriter<W>(pub W);
+
+impl<W> IoWriter<W> {
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    type Error = io::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.write_all(data)
+    }
+}
+
+/// Adapter to use a [`std::io::Read`] as a [`Read`]
+///
+/// Bytes are copied out of the stream, so they are always [`Bytes::Temporary`] and borrowed
+/// values like `&str` cannot be deserialized. Reaching the end of the stream in the middle of a
+/// value fails with an error of kind [`io::ErrorKind::UnexpectedEof`], which deserialization
+/// reports as [`Error::Io`](crate::Error::Io). [`from_reader`](crate::from_reader) uses this
+/// adapter.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use serde_scale::{Deserializer, IoReader};
+///
+/// let bytes = serde_scale::to_vec(&(1u8, "one")).unwrap();
+/// let mut deserializer = Deserializer::new(IoReader::new(&bytes[..]));
+/// let value = <(u8, String)>::deserialize(&mut deserializer).unwrap();
+/// assert_eq!(value, (1, String::from("one")));
+/// ```
+#[derive(Debug)]
+pub struct IoReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+}
+
+impl<R> IoReader<R> {
+    /// Returns a reader pulling bytes from `inner`
+    pub fn new(inner: R) -> Self {
+        Self { inner, buf: Vec::new() }
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<'a, R: io::Read> Read<'a> for IoReader<R> {
+    type Error = io::Error;
+
+    fn read_map<T, F>(&mut self, n: usize, f: F) -> Result<T, Self::Error>
+    where
+        F: FnOnce(Bytes<'a, '_>) -> T,
+    {
+        // Reading through `take` grows the buffer with the data actually received instead of
+        // allocating a length read from the input upfront.
+        self.buf.clear();
+        (&mut self.inner).take(n as u64).read_to_end(&mut self.buf)?;
+        if self.buf.len() < n {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(f(Bytes::Temporary(&self.buf)))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.read_exact(buf)
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `IpAddr` with a fixed layout
+//!
+//! The address is encoded as a discriminant byte followed by the octets of the address in network
+//! order:
+//! - 0 followed by 4 bytes for an IPv4 address
+//! - 1 followed by 16 bytes for an IPv6 address
+//!
+//! This bypasses the default `serde` representation of `IpAddr`, which depends on the serializer
+//! being human-readable. It is available in `no_std` builds with the `core_net` feature.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use std::net::{IpAddr, Ipv4Addr};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Peer {
+//!     #[serde(with = "serde_scale::ip_addr")]
+//!     addr: IpAddr,
+//! }
+//!
+//! let peer = Peer { addr: Ipv4Addr::new(192, 168, 0, 1).into() };
+//! let bytes = serde_scale::to_vec(&peer).unwrap();
+//! assert_eq!(bytes, [0, 192, 168, 0, 1]);
+//! assert_eq!(serde_scale::from_slice::<Peer>(&bytes).unwrap(), peer);
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use core::net::IpAddr;
+
+#[derive(Deserialize, Serialize)]
+enum Repr {
+    V4([u8; 4]),
+    V6([u8; 16]),
+}
+
+/// Serializes an IP address as a discriminant byte followed by its octets
+pub fn serialize<S>(addr: &IpAddr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match addr {
+        IpAddr::V4(a) => Repr::V4(a.octets()),
+        IpAddr::V6(a) => Repr::V6(a.octets()),
+    }
+    .serialize(serializer)
+}
+
+/// Deserializes an IP address from a discriminant byte followed by its octets
+pub fn deserialize<'de, D>(deserializer: D) -> Result<IpAddr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::V4(octets) => octets.into(),
+        Repr::V6(octets) => octets.into(),
+    })
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `Ipv4Addr` as 4 bytes
+//!
+//! The address is encoded as its octets in network order without a length prefix (i.e. like a
+//! `[u8; 4]`). This bypasses the default `serde` representation of `Ipv4Addr`, which depends on
+//! the serializer being human-readable. It is available in `no_std` builds with the `core_net`
+//! feature.
+//!
+//! ```rust
+//! use core::net::Ipv4Addr;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Host {
+//!     #[serde(with = "serde_scale::ipv4_addr")]
+//!     addr: Ipv4Addr,
+//! }
+//!
+//! let host = Host { addr: Ipv4Addr::new(10, 0, 0, 1) };
+//! let bytes = serde_scale::to_vec(&host).unwrap();
+//! assert_eq!(bytes, [10, 0, 0, 1]);
+//! assert_eq!(serde_scale::from_slice::<Host>(&bytes).unwrap(), host);
+//! ```
+
+use core::net::Ipv4Addr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes an IPv4 address as its 4 octets
+pub fn serialize<S>(addr: &Ipv4Addr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    addr.octets().serialize(serializer)
+}
+
+/// Deserializes an IPv4 address from its 4 octets
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Ipv4Addr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    <[u8; 4]>::deserialize(deserializer).map(Ipv4Addr::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_slice, ClosureWriter, Serializer};
+    use core::{convert::Infallible, net::Ipv4Addr};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Host(#[serde(with = "crate::ipv4_addr")] Ipv4Addr);
+
+    #[test]
+    fn ipv4_addr_roundtrips_as_4_bytes() {
+        let host = Host(Ipv4Addr::new(192, 168, 1, 254));
+        let mut out = [0; 8];
+        let mut len = 0;
+        let writer = ClosureWriter::new(|data: &[u8]| {
+            out[len..len + data.len()].copy_from_slice(data);
+            len += data.len();
+            Ok::<_, Infallible>(())
+        });
+        host.serialize(&mut Serializer::new(writer)).unwrap();
+        assert_eq!(out[..len], [192, 168, 1, 254]);
+        assert_eq!(from_slice::<Host>(&out[..len]).unwrap(), host);
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `Ipv6Addr` as 16 bytes
+//!
+//! The address is encoded as its octets in network order without a length prefix (i.e. like a
+//! `[u8; 16]`). This bypasses the default `serde` representation of `Ipv6Addr`, which depends on
+//! the serializer being human-readable. It is available in `no_std` builds with the `core_net`
+//! feature.
+//!
+//! ```rust
+//! use core::net::Ipv6Addr;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Host {
+//!     #[serde(with = "serde_scale::ipv6_addr")]
+//!     addr: Ipv6Addr,
+//! }
+//!
+//! let host = Host { addr: Ipv6Addr::LOCALHOST };
+//! let bytes = serde_scale::to_vec(&host).unwrap();
+//! assert_eq!(bytes, Ipv6Addr::LOCALHOST.octets());
+//! assert_eq!(serde_scale::from_slice::<Host>(&bytes).unwrap(), host);
+//! ```
+
+use core::net::Ipv6Addr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes an IPv6 address as its 16 octets
+pub fn serialize<S>(addr: &Ipv6Addr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    addr.octets().serialize(serializer)
+}
+
+/// Deserializes an IPv6 address from its 16 octets
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Ipv6Addr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    <[u8; 16]>::deserialize(deserializer).map(Ipv6Addr::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_slice, ClosureWriter, Serializer};
+    use core::{convert::Infallible, net::Ipv6Addr};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Host(#[serde(with = "crate::ipv6_addr")] Ipv6Addr);
+
+    #[test]
+    fn ipv6_addr_roundtrips_as_16_bytes() {
+        let host = Host(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0x0102));
+        let mut out = [0; 32];
+        let mut len = 0;
+        let writer = ClosureWriter::new(|data: &[u8]| {
+            out[len..len + data.len()].copy_from_slice(data);
+            len += data.len();
+            Ok::<_, Infallible>(())
+        });
+        host.serialize(&mut Serializer::new(writer)).unwrap();
+        assert_eq!(out[..len], [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x02]);
+        assert_eq!(from_slice::<Host>(&out[..len]).unwrap(), host);
+    }
+}
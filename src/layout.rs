@@ -0,0 +1,29 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+/// Field order of a structure
+///
+/// SCALE does not encode field names, so reordering the fields of a structure silently changes its
+/// encoding. `FIELDS` lists the fields in serialization order so that it can be checked against a
+/// known layout. With the `derive` feature, this trait can be derived, and
+/// `#[scale_layout(pinned(...))]` makes compilation fail if the fields no longer match:
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use serde_scale::ScaleLayout;
+///
+/// #[derive(ScaleLayout)]
+/// #[scale_layout(pinned("id", "name"))]
+/// struct User {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// assert_eq!(User::FIELDS, ["id", "name"]);
+/// # }
+/// ```
+///
+/// Fields of tuple structures are named after their index.
+pub trait ScaleLayout {
+    /// Field names in serialization order
+    const FIELDS: &'static [&'static str];
+}
@@ -28,11 +28,39 @@
 //! # Conformance
 //! `Option<bool>` is serialized as a single byte according to the SCALE encoding.
 //!
+//! Unsigned integers wrapped in `Compact` use the SCALE compact encoding. SCALE does not define
+//! a compact encoding for signed integers, so `Compact` cannot be serialized when wrapping them.
+//! Fields can also opt into the compact encoding with `#[serde(with = "serde_scale::compact")]`.
+//!
+//! `core::cmp::Reverse<T>` is serialized as the wrapped value. Only the iteration order of
+//! ordered collections of `Reverse` values differs from the one of the bare values.
+//!
+//! `Arc<str>`, `Arc<[u8]>`, `Rc<str>` and other shared pointers are serialized as the value they
+//! point to, e.g. `Arc<str>` like `String`. This requires the `rc` feature of `serde`.
+//!
+//! Enumeration variants marked `#[serde(skip)]` should come last. `serde` serializes a variant
+//! with its declaration index, counting skipped variants, but deserializes it with its index
+//! among the variants that are not skipped, which is also what `parity-scale-codec` does with
+//! `#[codec(skip)]`. Variants following a skipped one do not roundtrip.
+//!
 //! # Features
 //! `no_std` is supported by disabling default features.
 //!
 //! - `std`: Support for `std`. It is enabled by default.
 //! - `alloc`: Support for the `alloc` crate.
+//! - `bytes`: Deserialization from [`Buf`](https://docs.rs/bytes) implementations, e.g. chains of
+//!   `Bytes`, and serialization into `BufMut` implementations.
+//! - `chrono`: Helpers to (de)serialize [`DateTime<Utc>`](https://docs.rs/chrono) as milliseconds
+//!   since the Unix epoch.
+//! - `core_net`: Helpers to (de)serialize IP addresses from `core::net` without `std`. They are
+//!   always available with `std`.
+//! - `derive`: `#[derive(ScaleLayout)]` to list and pin the field order of structures.
+//! - `either`: Support for [`Either`](https://docs.rs/either), encoded as an enumeration whose
+//!   `Left` and `Right` variants have indices 0 and 1.
+//! - `heapless`: Serialization into [`heapless::Vec`](https://docs.rs/heapless), failing with
+//!   [`BufferOverflow`] when the capacity is exceeded.
+//! - `uuid`: Helpers to (de)serialize [`Uuid`](https://docs.rs/uuid) as 16 bytes.
+//! - `vec_of_le_ints`: Helpers to deserialize `Vec<u32>` and `Vec<u64>` in bulk.
 //!
 //! 🔖 Features enabled in build dependencies and proc-macros are also enabled for normal
 //! dependencies, which may cause `serde` to have its `std` feature on when it is not desired.
@@ -67,17 +95,84 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod args;
+#[cfg(feature = "bytes")]
+mod buf;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+pub mod compact;
 mod de;
+pub mod duration;
 mod err;
+#[cfg(feature = "std")]
+mod frame;
+mod framed;
+#[cfg(feature = "alloc")]
+mod hex;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(any(feature = "std", feature = "core_net"))]
+pub mod ip_addr;
+#[cfg(any(feature = "std", feature = "core_net"))]
+pub mod ipv4_addr;
+#[cfg(any(feature = "std", feature = "core_net"))]
+pub mod ipv6_addr;
+mod layout;
+mod long_tuple;
+#[cfg(feature = "alloc")]
+mod nested;
+#[cfg(feature = "std")]
+pub mod path;
+mod raw_bytes;
 mod read;
 mod ser;
+mod size;
+#[cfg(feature = "std")]
+pub mod system_time;
+#[cfg(all(test, feature = "alloc"))]
+mod testing;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+mod versioned;
+#[cfg(feature = "vec_of_le_ints")]
+pub mod vec_of_le_ints;
 mod write;
 
-pub use de::{from_slice, Deserializer};
-pub use err::{Error, OtherError};
+pub use args::ArgsBuilder;
+pub use compact::{Compact, CompactInt};
+pub use de::{
+    from_slice, from_slice_advancing, from_slice_limited, from_slice_strict, DeLimits,
+    Deserializer, Map,
+};
+pub use err::{Error, OtherError, PathSegment};
+#[cfg(feature = "std")]
+pub use frame::{FrameReader, FramingWriter};
+pub use framed::{FrameError, FramedReader};
+#[cfg(feature = "std")]
+pub use io::{IoReader, IoWriter};
+pub use layout::ScaleLayout;
+pub use long_tuple::LongTuple;
+pub use raw_bytes::RawBytes;
 pub use read::{Bytes, EndOfInput, Read};
-pub use ser::Serializer;
-pub use write::Write;
+pub use ser::{to_writer, Serializer};
+pub use size::{can_serialize, serialized_size, SizeLimitExceeded};
+pub use versioned::VersionedDeserializer;
+pub use write::{BufferOverflow, ClosureWriter, CountingWriter, SliceWriter, Write};
 
+#[cfg(feature = "bytes")]
+pub use buf::{BytesReader, BytesWriter};
+#[cfg(feature = "alloc")]
+pub use hex::{to_hex_into, HexWriter};
+#[cfg(feature = "alloc")]
+pub use nested::Nested;
 #[cfg(feature = "alloc")]
-pub use ser::to_vec;
+pub use read::{ClosureReader, VecReader};
+#[cfg(feature = "alloc")]
+pub use ser::{to_boxed_slice, to_vec, to_vec_with_capacity};
+#[cfg(feature = "std")]
+pub use de::from_reader;
+#[cfg(feature = "std")]
+pub use ser::to_vec_pooled;
+
+#[cfg(feature = "derive")]
+pub use serde_scale_derive::ScaleLayout;
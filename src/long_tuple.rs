@@ -0,0 +1,106 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{Error as _, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Fixed number of values of the same type serialized like a tuple
+///
+/// `serde` only implements its traits for tuples of up to 16 elements and arrays of up to 32
+/// elements. `LongTuple` supports any length. Like tuples, no length prefix is written.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde_scale::LongTuple;
+///
+/// let record = LongTuple([7u8; 40]);
+/// let bytes = serde_scale::to_vec(&record).unwrap();
+/// assert_eq!(bytes, [7; 40]);
+/// assert_eq!(serde_scale::from_slice::<LongTuple<u8, 40>>(&bytes).unwrap(), record);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LongTuple<T, const N: usize>(pub [T; N]);
+
+impl<T: Serialize, const N: usize> Serialize for LongTuple<T, N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for x in &self.0 {
+            tuple.serialize_element(x)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de, T, const N: usize> Deserialize<'de> for LongTuple<T, N>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(N, LongTupleVisitor(PhantomData))
+    }
+}
+
+struct LongTupleVisitor<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T, const N: usize> Visitor<'de> for LongTupleVisitor<T, N>
+where
+    T: Deserialize<'de>,
+{
+    type Value = LongTuple<T, N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a tuple of {} elements", N)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values: [Option<T>; N] = core::array::from_fn(|_| None);
+        for (i, x) in values.iter_mut().enumerate() {
+            *x = Some(seq.next_element()?.ok_or_else(|| A::Error::invalid_length(i, &self))?);
+        }
+        Ok(LongTuple(values.map(|x| x.expect("All elements were deserialized"))))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{from_slice, to_vec, LongTuple};
+
+    #[test]
+    fn long_tuple_serializes_like_array() {
+        let values = core::array::from_fn::<u32, 20, _>(|i| i as u32 * 1000);
+        assert_eq!(to_vec(&LongTuple(values)).unwrap(), to_vec(&values).unwrap());
+    }
+
+    #[test]
+    fn long_tuple_roundtrips() {
+        let record = LongTuple(core::array::from_fn::<u16, 20, _>(|i| i as u16));
+        let bytes = to_vec(&record).unwrap();
+        assert_eq!(bytes.len(), 40);
+        assert_eq!(from_slice::<LongTuple<u16, 20>>(&bytes).unwrap(), record);
+    }
+
+    #[test]
+    fn long_tuple_elements_need_not_be_default() {
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Id(core::num::NonZeroU8);
+
+        let record = LongTuple(core::array::from_fn::<_, 20, _>(|i| {
+            Id(core::num::NonZeroU8::new(i as u8 + 1).unwrap())
+        }));
+        let bytes = to_vec(&record).unwrap();
+        assert_eq!(from_slice::<LongTuple<Id, 20>>(&bytes).unwrap(), record);
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{DeserializeOwned, Visitor},
+    Deserialize, Serialize,
+};
+
+/// Value encoded with SCALE and stored as a byte array, i.e. encoded twice
+///
+/// The value is serialized to bytes, which are then serialized like a `Vec<u8>` with their
+/// compact length. Deserialization reads the byte array and deserializes the value from exactly
+/// these bytes, failing if some are left over. The inner value is (de)serialized with the options
+/// and limits of the outer [`Serializer`](crate::Serializer) or
+/// [`Deserializer`](crate::Deserializer). Other formats (de)serialize the value as is.
+///
+/// ```rust
+/// use serde_scale::Nested;
+///
+/// let bytes = serde_scale::to_vec(&Nested(1u16)).unwrap();
+/// assert_eq!(bytes, [8, 1, 0]);
+/// assert_eq!(serde_scale::from_slice::<Nested<u16>>(&bytes).unwrap(), Nested(1));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Nested<T>(pub T);
+
+pub(crate) const NAME: &str = "$serde_scale::Nested";
+
+impl<T: Serialize> Serialize for Nested<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &self.0)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Nested<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, NestedVisitor(PhantomData))
+    }
+}
+
+struct NestedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for NestedVisitor<T> {
+    type Value = Nested<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte array holding a SCALE-encoded value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Nested)
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `Path` and `PathBuf` as UTF-8 strings
+//!
+//! Paths are encoded like a `String`. SCALE strings must be valid UTF-8, so serializing a path
+//! that is not fails with [`Error::Other`](crate::Error::Other) naming the offending path.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use std::path::PathBuf;
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Config {
+//!     #[serde(with = "serde_scale::path")]
+//!     root: PathBuf,
+//! }
+//!
+//! let config = Config { root: "/var/lib/app".into() };
+//! let bytes = serde_scale::to_vec(&config).unwrap();
+//! assert_eq!(serde_scale::from_slice::<Config>(&bytes).unwrap(), config);
+//! ```
+
+use serde::{ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::path::{Path, PathBuf};
+
+/// Serializes a path as a UTF-8 string
+pub fn serialize<S, P>(path: &P, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    P: AsRef<Path> + ?Sized,
+{
+    let path = path.as_ref();
+    match path.to_str() {
+        Some(s) => s.serialize(serializer),
+        None => Err(S::Error::custom(format!(
+            "Path {} cannot be serialized because it is not valid UTF-8",
+            path.display(),
+        ))),
+    }
+}
+
+/// Deserializes a path from a UTF-8 string
+pub fn deserialize<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(PathBuf::from)
+}
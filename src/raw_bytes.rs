@@ -0,0 +1,112 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use core::{convert::TryInto, fmt, ops::Deref};
+use serde::{
+    de::{Error as _, SeqAccess, Visitor},
+    ser::SerializeTupleStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Fixed-size byte array encoded as its bytes, without a length prefix
+///
+/// This is meant for opaque fields of known size like hashes, and matches the SCALE encoding of
+/// `[u8; N]` for any `N`. When deserializing with this crate's
+/// [`Deserializer`](crate::Deserializer), all bytes are read at once.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde::{Deserialize, Serialize};
+/// use serde_scale::RawBytes;
+///
+/// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// struct Block {
+///     number: u32,
+///     hash: RawBytes<4>,
+/// }
+///
+/// let block = Block { number: 1, hash: RawBytes([0xab; 4]) };
+/// let bytes = serde_scale::to_vec(&block).unwrap();
+/// assert_eq!(bytes, [1, 0, 0, 0, 0xab, 0xab, 0xab, 0xab]);
+/// assert_eq!(serde_scale::from_slice::<Block>(&bytes).unwrap(), block);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RawBytes<const N: usize>(pub [u8; N]);
+
+pub(crate) const NAME: &str = "$serde_scale::RawBytes";
+
+impl<const N: usize> From<[u8; N]> for RawBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> From<RawBytes<N>> for [u8; N] {
+    fn from(bytes: RawBytes<N>) -> Self {
+        bytes.0
+    }
+}
+
+impl<const N: usize> Deref for RawBytes<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for RawBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Serialize for RawBytes<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple_struct(NAME, N)?;
+        for b in &self.0 {
+            tuple.serialize_field(b)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for RawBytes<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple_struct(NAME, N, RawBytesVisitor)
+    }
+}
+
+struct RawBytesVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for RawBytesVisitor<N> {
+    type Value = RawBytes<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes", N)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.try_into().map(RawBytes).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = [0; N];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(i, &self))?;
+        }
+        Ok(RawBytes(bytes))
+    }
+}
@@ -1,5 +1,7 @@
 // Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug, Display},
     ops::Deref,
@@ -24,6 +26,14 @@ pub trait Read<'a> {
             buf.copy_from_slice(&bytes);
         })
     }
+
+    /// Returns the number of bytes left if known
+    ///
+    /// Readers that cannot know how many bytes are left (e.g. streams) return `None`, which is the
+    /// default.
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
 }
 
 impl<'a, T: Read<'a> + ?Sized> Read<'a> for &'_ mut T {
@@ -39,6 +49,10 @@ impl<'a, T: Read<'a> + ?Sized> Read<'a> for &'_ mut T {
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
         (**self).read_exact(buf)
     }
+
+    fn remaining(&self) -> Option<usize> {
+        (**self).remaining()
+    }
 }
 
 impl<'a> Read<'a> for &'a [u8] {
@@ -55,6 +69,149 @@ impl<'a> Read<'a> for &'a [u8] {
         *self = remaining;
         Ok(f(Bytes::Persistent(consumed)))
     }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+/// Reader over the content of a borrowed `Vec<u8>`
+///
+/// `&Vec<u8>` cannot be advanced in place, so this adapter keeps the unread part of the vector.
+/// Bytes are yielded as [`Bytes::Persistent`], so borrowed values point into the vector.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde::Deserialize;
+/// use serde_scale::{Deserializer, VecReader};
+///
+/// let bytes = serde_scale::to_vec(&"vec").unwrap();
+/// let s = <&str>::deserialize(&mut Deserializer::new(VecReader::from(&bytes))).unwrap();
+/// assert_eq!(s, "vec");
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug)]
+pub struct VecReader<'a> {
+    remaining: &'a [u8],
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> VecReader<'a> {
+    /// Returns a reader starting at the beginning of `v`
+    pub fn new(v: &'a Vec<u8>) -> Self {
+        Self { remaining: v }
+    }
+
+    /// Returns the bytes not read yet
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.remaining
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<&'a Vec<u8>> for VecReader<'a> {
+    fn from(v: &'a Vec<u8>) -> Self {
+        Self::new(v)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Read<'a> for VecReader<'a> {
+    type Error = EndOfInput;
+
+    fn read_map<R, F>(&mut self, n: usize, f: F) -> Result<R, Self::Error>
+    where
+        F: FnOnce(Bytes<'a, '_>) -> R,
+    {
+        self.remaining.read_map(n, f)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+/// Reader pulling bytes from a closure
+///
+/// The closure must fill the whole buffer it is given or fail. Bytes are yielded as
+/// [`Bytes::Temporary`], so borrowed values cannot be deserialized from this reader.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde::Deserialize;
+/// use serde_scale::{ClosureReader, Deserializer, EndOfInput};
+///
+/// let mut input = &[5u8, 0][..];
+/// let reader = ClosureReader::new(|buf: &mut [u8]| {
+///     if buf.len() > input.len() {
+///         return Err(EndOfInput);
+///     }
+///     let (head, tail) = input.split_at(buf.len());
+///     buf.copy_from_slice(head);
+///     input = tail;
+///     Ok(())
+/// });
+/// assert_eq!(u16::deserialize(&mut Deserializer::new(reader)).unwrap(), 5);
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub struct ClosureReader<F> {
+    f: F,
+    buf: Vec<u8>,
+}
+
+/// Number of bytes `ClosureReader` requests at first when reading a large span
+#[cfg(feature = "alloc")]
+const CLOSURE_READER_CHUNK: usize = 4096;
+
+#[cfg(feature = "alloc")]
+impl<F> ClosureReader<F> {
+    /// Returns a reader calling `f` to fill buffers
+    pub fn new(f: F) -> Self {
+        Self { f, buf: Vec::new() }
+    }
+
+    /// Returns the underlying closure
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F> Debug for ClosureReader<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClosureReader").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, F, E> Read<'a> for ClosureReader<F>
+where
+    F: FnMut(&mut [u8]) -> Result<(), E>,
+    E: Debug + Display,
+{
+    type Error = E;
+
+    fn read_map<R, G>(&mut self, n: usize, f: G) -> Result<R, Self::Error>
+    where
+        G: FnOnce(Bytes<'a, '_>) -> R,
+    {
+        // Grow the buffer as bytes arrive so that a hostile length cannot trigger a huge
+        // allocation before the closure runs out of input.
+        self.buf.clear();
+        while self.buf.len() < n {
+            let start = self.buf.len();
+            let chunk = (n - start).min(start.max(CLOSURE_READER_CHUNK));
+            self.buf.resize(start + chunk, 0);
+            (self.f)(&mut self.buf[start..])?;
+        }
+        Ok(f(Bytes::Temporary(&self.buf)))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (self.f)(buf)
+    }
 }
 
 /// Bytes borrowed from the deserializer or valid only for the duration of the call to `read_map`
@@ -88,3 +245,131 @@ impl Display for EndOfInput {
 
 #[cfg(feature = "std")]
 impl std::error::Error for EndOfInput {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_slice, Bytes, Read};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        data: &'a [u8],
+    }
+
+    #[test]
+    fn slice_reader_yields_persistent_bytes() {
+        let input = [1u8, 2, 3];
+        let mut reader = &input[..];
+        let persistent = reader.read_map(2, |bytes| match bytes {
+            Bytes::Persistent(b) => Some(b),
+            Bytes::Temporary(_) => None,
+        });
+        assert_eq!(persistent.unwrap(), Some(&input[..2]));
+        assert_eq!(reader, [3]);
+    }
+
+    #[test]
+    fn str_is_borrowed_from_slice() {
+        let input = [12, b'f', b'o', b'o'];
+        let s = from_slice::<&str>(&input).unwrap();
+        assert_eq!(s, "foo");
+        assert_eq!(s.as_ptr(), input[1..].as_ptr());
+    }
+
+    #[test]
+    fn bytes_are_borrowed_from_slice() {
+        let input = [8, 1, 2];
+        let b = from_slice::<&[u8]>(&input).unwrap();
+        assert_eq!(b, [1, 2]);
+        assert_eq!(b.as_ptr(), input[1..].as_ptr());
+    }
+
+    #[test]
+    fn struct_borrows_from_slice() {
+        let input = [8, b'h', b'i', 4, 7];
+        let value = from_slice::<Borrowed<'_>>(&input).unwrap();
+        assert_eq!(value, Borrowed { name: "hi", data: &[7] });
+        assert_eq!(value.data.as_ptr(), input[4..].as_ptr());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn str_is_borrowed_from_vec() {
+        use crate::{Deserializer, VecReader};
+
+        let input = alloc::vec![12, b'f', b'o', b'o', 1];
+        let mut reader = VecReader::from(&input);
+        let s = <&str>::deserialize(&mut Deserializer::new(&mut reader)).unwrap();
+        assert_eq!(s, "foo");
+        assert_eq!(s.as_ptr(), input[1..].as_ptr());
+        assert_eq!(reader.as_slice(), [1]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn closure_reader_pulls_from_queue() {
+        use crate::{ClosureReader, Deserializer, EndOfInput, Error};
+        use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+        let mut queue = crate::to_vec(&(String::from("abc"), 7u32))
+            .unwrap()
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        let mut pull = |buf: &mut [u8]| {
+            let n = buf.len();
+            if n > queue.len() {
+                return Err(EndOfInput);
+            }
+            buf.iter_mut().zip(queue.drain(..n)).for_each(|(dst, src)| *dst = src);
+            Ok(())
+        };
+        let mut deserializer = Deserializer::new(ClosureReader::new(&mut pull));
+        let value = <(String, u32)>::deserialize(&mut deserializer).unwrap();
+        assert_eq!(value, (String::from("abc"), 7));
+        let res = <Vec<u8>>::deserialize(&mut deserializer);
+        assert!(matches!(res, Err(Error::Io(EndOfInput))));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn closure_reader_does_not_allocate_declared_length_upfront() {
+        use crate::{ClosureReader, Deserializer, EndOfInput, Error};
+        use alloc::vec::Vec;
+
+        // Length of 2^40 followed by a few bytes
+        let mut input = &[0x0b, 0, 0, 0, 0, 0, 0x01, 1, 2, 3][..];
+        let mut requested = Vec::new();
+        let reader = ClosureReader::new(|buf: &mut [u8]| {
+            requested.push(buf.len());
+            if buf.len() > input.len() {
+                return Err(EndOfInput);
+            }
+            let (head, tail) = input.split_at(buf.len());
+            buf.copy_from_slice(head);
+            input = tail;
+            Ok(())
+        });
+        let res = <Vec<u8>>::deserialize(&mut Deserializer::new(reader));
+        assert!(matches!(res, Err(Error::Io(EndOfInput))));
+        assert!(requested.iter().all(|&n| n <= 4096), "{:?}", requested);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn closure_reader_reads_long_spans_in_chunks() {
+        use crate::{ClosureReader, Deserializer};
+        use alloc::vec::Vec;
+
+        let bytes = crate::to_vec(&alloc::vec![7u8; 20_000]).unwrap();
+        let mut input = &bytes[..];
+        let reader = ClosureReader::new(|buf: &mut [u8]| {
+            let (head, tail) = input.split_at(buf.len());
+            buf.copy_from_slice(head);
+            input = tail;
+            Ok::<_, crate::EndOfInput>(())
+        });
+        let value = <Vec<u8>>::deserialize(&mut Deserializer::new(reader)).unwrap();
+        assert_eq!(value, [7; 20_000]);
+    }
+}
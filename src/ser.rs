@@ -8,41 +8,356 @@ use core::{
 };
 
 #[cfg(feature = "alloc")]
-use alloc::vec::Vec;
+use crate::OtherError;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use serde::de::DeserializeOwned;
+
+/// Serializes a value using the SCALE encoding into the given writer
+///
+/// This does not require `alloc`, and allows streaming into any [`Write`] sink like a fixed
+/// buffer or a serial port. Passing `&mut W` keeps the writer usable afterwards, which is how
+/// [`to_vec`] is implemented.
+pub fn to_writer<W, T>(w: W, x: &T) -> Result<(), Error<W::Error>>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    x.serialize(&mut Serializer::new(w))
+}
 
 /// Serializes a value using the SCALE encoding
 #[cfg(feature = "alloc")]
 pub fn to_vec<T: Serialize>(x: &T) -> Result<Vec<u8>, Error<core::convert::Infallible>> {
-    let mut serializer = Serializer::new(Vec::new());
-    x.serialize(&mut serializer)?;
-    Ok(serializer.0)
+    to_vec_with_capacity(x, 0)
+}
+
+/// Serializes a value using the SCALE encoding into a `Vec` with the given initial capacity
+///
+/// Starting with enough capacity avoids growing the output several times when serializing large
+/// values. The exact size can be obtained with a cheap dry run using
+/// [`serialized_size`](crate::serialized_size), at the cost of serializing the value twice:
+///
+/// ```rust
+/// let value = vec![7u32; 10_000];
+/// let size = serde_scale::serialized_size(&value).unwrap();
+/// let out = serde_scale::to_vec_with_capacity(&value, size).unwrap();
+/// assert_eq!(out.len(), size);
+/// assert_eq!(out, serde_scale::to_vec(&value).unwrap());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_vec_with_capacity<T>(
+    x: &T,
+    capacity: usize,
+) -> Result<Vec<u8>, Error<core::convert::Infallible>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = Vec::with_capacity(capacity);
+    to_writer(&mut out, x)?;
+    Ok(out)
+}
+
+/// Serializes a value using the SCALE encoding into a boxed slice without excess capacity
+#[cfg(feature = "alloc")]
+pub fn to_boxed_slice<T: Serialize>(x: &T) -> Result<Box<[u8]>, Error<core::convert::Infallible>> {
+    to_vec(x).map(Vec::into_boxed_slice)
+}
+
+/// Serializes a value using the SCALE encoding, reusing a thread-local buffer
+///
+/// The value is serialized into a buffer kept by the current thread across calls, and the result
+/// is copied into a `Vec` of the exact size. This avoids growing the output several times, which
+/// benefits code serializing many values. Calls made while the buffer is in use (i.e. from the
+/// `Serialize` implementation of the value) fall back to [`to_vec`]. The buffer keeps the
+/// capacity needed by the largest value serialized on the thread.
+#[cfg(feature = "std")]
+pub fn to_vec_pooled<T: Serialize>(x: &T) -> Result<Vec<u8>, Error<core::convert::Infallible>> {
+    std::thread_local! {
+        static BUFFER: core::cell::RefCell<Vec<u8>> = const { core::cell::RefCell::new(Vec::new()) };
+    }
+
+    BUFFER.with(|buffer| {
+        let mut buffer = match buffer.try_borrow_mut() {
+            Ok(buffer) => buffer,
+            Err(_) => return to_vec(x),
+        };
+        buffer.clear();
+        let mut serializer = Serializer::new(&mut *buffer);
+        x.serialize(&mut serializer)?;
+        Ok(buffer.to_vec())
+    })
 }
 
 /// Serializer for the SCALE encoding
-#[derive(Debug)]
-pub struct Serializer<W>(W);
+pub struct Serializer<W> {
+    out: W,
+    consumed: usize,
+    max_write_chunk: Option<usize>,
+    leb128_lengths: bool,
+    sorted_maps: bool,
+    unit_placeholder: bool,
+    /// Sorts buffered map entries instead of comparing their key bytes
+    #[cfg(feature = "alloc")]
+    key_order: Option<Arc<SortEntries>>,
+}
+
+/// Serialized map entries as (key, value) pairs
+#[cfg(feature = "alloc")]
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[cfg(feature = "alloc")]
+type SortEntries = dyn Fn(&mut Entries, KeyFormat) -> Result<(), OtherError> + Send + Sync;
+
+/// Options of a serializer affecting how keys are read back
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy)]
+struct KeyFormat {
+    leb128_lengths: bool,
+    unit_placeholder: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl KeyFormat {
+    /// Deserializes a key from all of `bytes`
+    fn read<K: DeserializeOwned>(self, bytes: &[u8]) -> Option<K> {
+        let mut deserializer = crate::Deserializer::new(bytes);
+        if self.leb128_lengths {
+            deserializer = deserializer.with_leb128_lengths();
+        }
+        if self.unit_placeholder {
+            deserializer = deserializer.with_unit_placeholder();
+        }
+        let key = K::deserialize(&mut deserializer).ok()?;
+        if deserializer.is_empty() { Some(key) } else { None }
+    }
+}
+
+/// Sorts entries by their keys read as `K`, failing if any cannot be read as `K`
+///
+/// Entries with equivalent keys are ordered by key bytes to keep the output deterministic.
+#[cfg(feature = "alloc")]
+fn sort_entries_by<K, F>(
+    entries: &mut Entries,
+    format: KeyFormat,
+    cmp: &F,
+) -> Result<(), OtherError>
+where
+    K: DeserializeOwned,
+    F: Fn(&K, &K) -> Ordering,
+{
+    let keys = entries
+        .iter()
+        .map(|(key, _)| format.read::<K>(key))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| OtherError::from("Map key cannot be read back as the compared key type"))?;
+    let mut keyed = keys.into_iter().zip(entries.drain(..)).collect::<Vec<_>>();
+    keyed.sort_by(|a, b| cmp(&a.0, &b.0).then_with(|| (a.1).0.cmp(&(b.1).0)));
+    entries.extend(keyed.into_iter().map(|(_, entry)| entry));
+    Ok(())
+}
 
 impl<W: Write> Serializer<W> {
     /// Returns a serializer using the given writer
     pub fn new(out: W) -> Self {
-        Self(out)
+        Self {
+            out,
+            consumed: 0,
+            max_write_chunk: None,
+            leb128_lengths: false,
+            sorted_maps: false,
+            unit_placeholder: false,
+            #[cfg(feature = "alloc")]
+            key_order: None,
+        }
+    }
+
+    /// Returns a serializer that never writes more than `n` bytes at once
+    ///
+    /// Large byte arrays and strings are split across several calls to [`Write::write`].
+    ///
+    /// # Panics
+    /// Panics if `n` is 0.
+    pub fn with_max_write_chunk(self, n: usize) -> Self {
+        assert!(n > 0, "Maximum write chunk size must not be 0");
+        Self {
+            max_write_chunk: Some(n),
+            ..self
+        }
+    }
+
+    /// Returns a serializer encoding collection lengths as unsigned LEB128
+    ///
+    /// This deviates from SCALE, which uses its compact encoding for lengths, and is meant for
+    /// interoperability with systems expecting LEB128 varints. Fixed-size integers and values
+    /// wrapped in [`Compact`](crate::Compact) are unaffected. The output must be read with
+    /// [`Deserializer::with_leb128_lengths`](crate::Deserializer::with_leb128_lengths).
+    pub fn with_leb128_lengths(self) -> Self {
+        Self {
+            leb128_lengths: true,
+            ..self
+        }
+    }
+
+    /// Returns a serializer writing map entries sorted by the bytes of their serialized keys
+    ///
+    /// This makes the output deterministic for maps whose iteration order is not, like `HashMap`,
+    /// even if the keys do not implement `Ord`. Each entry is buffered until the map ends.
+    #[cfg(feature = "alloc")]
+    pub fn with_sorted_maps(self) -> Self {
+        Self {
+            sorted_maps: true,
+            ..self
+        }
+    }
+
+    /// Returns a serializer writing map entries sorted by their keys according to `cmp`
+    ///
+    /// This is like [`with_sorted_maps`](Self::with_sorted_maps) but for protocols requiring a
+    /// specific key order, e.g. numeric regardless of the encoding. Keys are opaque while
+    /// serializing, so each entry is buffered and its key is deserialized back as a `K` to be
+    /// compared. Entries whose keys compare equal are ordered by the bytes of their keys.
+    /// Serialization fails with [`Error::Other`] if a key cannot be deserialized as `K`, e.g. in a
+    /// nested map with another key type.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use serde::Serialize;
+    /// use serde_scale::Serializer;
+    /// use std::collections::HashMap;
+    ///
+    /// let map = vec![(256u16, 'a'), (2, 'b')].into_iter().collect::<HashMap<_, _>>();
+    /// let mut serializer = Serializer::new(Vec::new()).with_sorted_maps_by(u16::cmp);
+    /// map.serialize(&mut serializer).unwrap();
+    /// let bytes = serializer.into_inner();
+    /// assert_eq!(bytes[1..3], [2, 0]);
+    /// # }
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn with_sorted_maps_by<K, F>(self, cmp: F) -> Self
+    where
+        K: DeserializeOwned,
+        F: Fn(&K, &K) -> Ordering + Send + Sync + 'static,
+    {
+        Self {
+            sorted_maps: true,
+            key_order: Some(Arc::new(move |entries: &mut Entries, format: KeyFormat| {
+                sort_entries_by(entries, format, &cmp)
+            })),
+            ..self
+        }
+    }
+
+    /// Returns a serializer writing a single 0 byte for `()` and unit structs
+    ///
+    /// SCALE encodes units as nothing, but some schemas expect a placeholder byte. The output
+    /// must be read with
+    /// [`Deserializer::with_unit_placeholder`](crate::Deserializer::with_unit_placeholder).
+    pub fn with_unit_placeholder(self) -> Self {
+        Self {
+            unit_placeholder: true,
+            ..self
+        }
     }
 
     /// Returns the underlying writer
     pub fn into_inner(self) -> W {
-        self.0
+        self.out
     }
 
-    fn serialize_compact(&mut self, v: u64) -> Result<(), Error<W::Error>> {
+    /// Returns the number of bytes written so far
+    pub fn bytes_written(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns the underlying writer and the number of bytes written to it
+    pub fn finish(self) -> (W, usize) {
+        (self.out, self.consumed)
+    }
+
+    /// Writes already encoded bytes verbatim, without a length prefix
+    ///
+    /// This allows splicing a cached fragment into the output instead of serializing its value
+    /// again. The bytes are not checked and must be a valid SCALE encoding of whatever value is
+    /// expected at this position.
+    pub fn write_raw(&mut self, bytes: &[u8]) -> Result<(), Error<W::Error>> {
+        self.write(bytes)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Error<W::Error>> {
+        match self.max_write_chunk {
+            Some(n) => {
+                for chunk in data.chunks(n) {
+                    self.out.write(chunk)?;
+                }
+            }
+            None => self.out.write(data)?,
+        }
+        self.consumed += data.len();
+        Ok(())
+    }
+
+    /// Returns a serializer to a buffer, with the same options as this one
+    #[cfg(feature = "alloc")]
+    fn buffered(&self) -> Serializer<Buffer<W::Error>> {
+        Serializer {
+            out: Buffer::default(),
+            consumed: 0,
+            max_write_chunk: None,
+            leb128_lengths: self.leb128_lengths,
+            sorted_maps: self.sorted_maps,
+            unit_placeholder: self.unit_placeholder,
+            key_order: self.key_order.clone(),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn key_format(&self) -> KeyFormat {
+        KeyFormat {
+            leb128_lengths: self.leb128_lengths,
+            unit_placeholder: self.unit_placeholder,
+        }
+    }
+
+    fn serialize_len(&mut self, len: usize) -> Result<(), Error<W::Error>> {
+        let len = u64::try_from(len).map_err(|_| Error::CollectionTooLargeToSerialize { len })?;
+        if self.leb128_lengths {
+            self.serialize_leb128(len)
+        } else {
+            self.serialize_compact(len.into())
+        }
+    }
+
+    fn serialize_leb128(&mut self, mut v: u64) -> Result<(), Error<W::Error>> {
+        let mut bytes = [0u8; 10];
+        let mut n = 0;
+        loop {
+            let low = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                bytes[n] = low;
+                n += 1;
+                break;
+            }
+            bytes[n] = low | 0x80;
+            n += 1;
+        }
+        self.write(&bytes[..n])
+    }
+
+    fn serialize_compact(&mut self, v: u128) -> Result<(), Error<W::Error>> {
         if v < 0x40 {
             let bytes = [(v << 2 & 0xff) as u8];
-            Ok(self.0.write(&bytes)?)
+            self.write(&bytes)
         } else if v < 0x4000 {
             let bytes = [
                 ((v << 2 | 0x1) & 0xff) as u8,
                 (v >> 6 & 0xff) as u8,
             ];
-            Ok(self.0.write(&bytes)?)
+            self.write(&bytes)
         } else if v < 0x4000_0000 {
             let high = v >> 6;
             let bytes = [
@@ -51,32 +366,31 @@ impl<W: Write> Serializer<W> {
                 (high >> 8 & 0xff) as u8,
                 (high >> 16 & 0xff) as u8,
             ];
-            Ok(self.0.write(&bytes)?)
+            self.write(&bytes)
         } else {
-            let mut bytes = [0u8; 9];
-            let mut v = v;
-            let src = core::iter::from_fn(|| {
-                if v == 0 { return None; }
-                let low = (v & 0xff) as u8;
-                v >>= 8;
-                Some(low)
-            });
-            let end = bytes.iter_mut()
-                .skip(1)
-                .zip(src)
-                .enumerate()
-                .map(|(i, (dst, src))| {
-                    *dst = src;
-                    i
-                })
-                .last()
-                .unwrap() + 1;
-            bytes[0] = (end - 4 << 2 & 0x3) as u8;
-            Ok(self.0.write(&bytes[..end + 1])?)
+            let len = 16 - (v.leading_zeros() / 8) as usize;
+            let mut bytes = [0u8; 17];
+            bytes[0] = (((len - 4) as u8) << 2) | 0x3;
+            bytes[1..=len].copy_from_slice(&v.to_le_bytes()[..len]);
+            self.write(&bytes[..=len])
         }
     }
 }
 
+impl<W> Debug for Serializer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Serializer");
+        s.field("bytes_written", &self.consumed)
+            .field("max_write_chunk", &self.max_write_chunk)
+            .field("leb128_lengths", &self.leb128_lengths)
+            .field("sorted_maps", &self.sorted_maps)
+            .field("unit_placeholder", &self.unit_placeholder);
+        #[cfg(feature = "alloc")]
+        s.field("key_order", &self.key_order.is_some());
+        s.finish_non_exhaustive()
+    }
+}
+
 impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     type Ok = ();
     type Error = Error<W::Error>;
@@ -84,7 +398,7 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     type SerializeTuple = Compound<'a, W>;
     type SerializeTupleStruct = Compound<'a, W>;
     type SerializeTupleVariant = Compound<'a, W>;
-    type SerializeMap = Compound<'a, W>;
+    type SerializeMap = MapCompound<'a, W>;
     type SerializeStruct = Compound<'a, W>;
     type SerializeStructVariant = Compound<'a, W>;
 
@@ -93,35 +407,43 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.write(&v.to_le_bytes())?)
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.write(&v.to_le_bytes())
     }
 
     fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> {
@@ -142,9 +464,8 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
         let len = v.len();
-        let len = u64::try_from(len).map_err(|_| Error::CollectionTooLargeToSerialize { len })?;
-        self.serialize_compact(len)?;
-        Ok(self.0.write(v)?)
+        self.serialize_len(len)?;
+        self.write(v)
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -163,7 +484,11 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        if self.unit_placeholder {
+            self.write(&[0])
+        } else {
+            Ok(())
+        }
     }
 
     fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
@@ -188,12 +513,24 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
 
     fn serialize_newtype_struct<T>(
         self,
-        _: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize + ?Sized,
     {
+        if name == crate::compact::NAME {
+            let v = crate::compact::unsigned_value(value).ok_or_else(|| {
+                Error::Other("Compact encoding requires an unsigned integer".into())
+            })?;
+            return self.serialize_compact(v);
+        }
+        #[cfg(feature = "alloc")]
+        if name == crate::nested::NAME {
+            let mut buffered = self.buffered();
+            value.serialize(&mut buffered)?;
+            return self.serialize_bytes(&buffered.out.0);
+        }
         value.serialize(self)
     }
 
@@ -213,8 +550,7 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let len = len.ok_or(Error::LengthNeeded)?;
-        let len = u64::try_from(len).map_err(|_| Error::CollectionTooLargeToSerialize { len })?;
-        self.serialize_compact(len)?;
+        self.serialize_len(len)?;
         Ok(Compound(self))
     }
 
@@ -243,9 +579,8 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         let len = len.ok_or(Error::LengthNeeded)?;
-        let len = u64::try_from(len).map_err(|_| Error::CollectionTooLargeToSerialize { len })?;
-        self.serialize_compact(len)?;
-        Ok(Compound(self))
+        self.serialize_len(len)?;
+        Ok(MapCompound::new(self))
     }
 
     fn serialize_struct(
@@ -274,13 +609,58 @@ impl<'a, W: Write> serde::Serializer for &'a mut Serializer<W> {
     {
         Err(serde::ser::Error::custom("Unsupported `collect_str` without `alloc` feature"))
     }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 }
 
 mod compound {
+    #[cfg(feature = "alloc")]
+    use alloc::vec::Vec;
+
     pub struct Compound<'a, W>(pub &'a mut super::Serializer<W>);
+
+    pub struct MapCompound<'a, W> {
+        pub(super) ser: &'a mut super::Serializer<W>,
+        /// Serialized keys and values, when entries are sorted
+        #[cfg(feature = "alloc")]
+        pub(super) entries: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    }
+}
+
+use compound::{Compound, MapCompound};
+
+impl<'a, W> MapCompound<'a, W> {
+    fn new(ser: &'a mut Serializer<W>) -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            entries: if ser.sorted_maps { Some(Vec::new()) } else { None },
+            ser,
+        }
+    }
 }
 
-use compound::Compound;
+/// Writer to memory whose error type matches the one of another writer
+#[cfg(feature = "alloc")]
+struct Buffer<E>(Vec<u8>, core::marker::PhantomData<E>);
+
+#[cfg(feature = "alloc")]
+impl<E> Default for Buffer<E> {
+    fn default() -> Self {
+        Buffer(Vec::new(), core::marker::PhantomData)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Debug + Display> Write for Buffer<E> {
+    type Error = E;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), E> {
+        self.0.extend(data);
+        Ok(())
+    }
+}
 
 impl<W: Write> serde::ser::SerializeSeq for Compound<'_, W> {
     type Ok = ();
@@ -346,7 +726,7 @@ impl<W: Write> serde::ser::SerializeTupleVariant for Compound<'_, W> {
     }
 }
 
-impl<W: Write> serde::ser::SerializeMap for Compound<'_, W> {
+impl<W: Write> serde::ser::SerializeMap for MapCompound<'_, W> {
     type Ok = ();
     type Error = Error<W::Error>;
 
@@ -354,18 +734,44 @@ impl<W: Write> serde::ser::SerializeMap for Compound<'_, W> {
     where
         T: Serialize + ?Sized,
     {
-        self.0.serialize_compact(2)?;
-        key.serialize(&mut *self.0)
+        #[cfg(feature = "alloc")]
+        if let Some(entries) = &mut self.entries {
+            let mut buffered = self.ser.buffered();
+            key.serialize(&mut buffered)?;
+            entries.push((buffered.out.0, Vec::new()));
+            return Ok(());
+        }
+        key.serialize(&mut *self.ser)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(&mut *self.0)
+        #[cfg(feature = "alloc")]
+        if let Some(entries) = &mut self.entries {
+            let mut buffered = self.ser.buffered();
+            value.serialize(&mut buffered)?;
+            if let Some(entry) = entries.last_mut() {
+                entry.1 = buffered.out.0;
+            }
+            return Ok(());
+        }
+        value.serialize(&mut *self.ser)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if let Some(mut entries) = self.entries {
+            match &self.ser.key_order {
+                Some(sort) => sort(&mut entries, self.ser.key_format()).map_err(Error::Other)?,
+                None => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+            }
+            for (key, value) in entries {
+                self.ser.write(&key)?;
+                self.ser.write(&value)?;
+            }
+        }
         Ok(())
     }
 }
@@ -596,11 +1002,45 @@ impl serde::Serializer for OptionalBoolSerializer {
     {
         Err(VoidError)
     }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(all(test, feature = "alloc"))]
 mod tests {
-    use crate::to_vec;
+    use crate::{to_vec, Serializer, Write};
+    use alloc::{format, string::String, vec, vec::Vec};
+    use core::convert::Infallible;
+    use serde::{Serialize, Serializer as _};
+
+    struct ChunkChecker {
+        max: usize,
+        out: Vec<u8>,
+        writes: usize,
+    }
+
+    impl ChunkChecker {
+        fn new(max: usize) -> Self {
+            Self {
+                max,
+                out: Vec::new(),
+                writes: 0,
+            }
+        }
+    }
+
+    impl Write for ChunkChecker {
+        type Error = Infallible;
+
+        fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+            assert!(data.len() <= self.max, "Wrote {} bytes at once", data.len());
+            self.out.extend(data);
+            self.writes += 1;
+            Ok(())
+        }
+    }
 
     #[test]
     fn none_bool_serializes_as_0() {
@@ -616,4 +1056,108 @@ mod tests {
     fn some_false_serializes_as_2() {
         assert_eq!(to_vec(&Some(false)).unwrap(), [2]);
     }
+
+    #[test]
+    fn bytes_written_matches_output_length() {
+        let mut serializer = Serializer::new(Vec::new());
+        (42u32, String::from("foo"), Some(7u8)).serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.bytes_written(), 10);
+        let (out, n) = serializer.finish();
+        assert_eq!(n, out.len());
+    }
+
+    #[test]
+    fn writes_are_split_in_chunks() {
+        let data = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        let mut serializer = Serializer::new(ChunkChecker::new(64)).with_max_write_chunk(64);
+        serializer.serialize_bytes(&data).unwrap();
+        let checker = serializer.into_inner();
+        let mut expected = vec![0xa1, 0x0f];
+        expected.extend(&data);
+        assert_eq!(checker.out, expected);
+        assert_eq!(checker.writes, 17);
+    }
+
+    #[test]
+    fn writes_are_not_split_by_default() {
+        let s = "a".repeat(1000);
+        let mut serializer = Serializer::new(ChunkChecker::new(usize::MAX));
+        s.serialize(&mut serializer).unwrap();
+        let checker = serializer.into_inner();
+        assert_eq!(checker.out.len(), 1002);
+        assert_eq!(checker.writes, 2);
+    }
+
+    #[test]
+    fn debug_shows_options() {
+        let serializer = Serializer::new(Vec::new()).with_max_write_chunk(16);
+        let debug = format!("{:?}", serializer);
+        assert!(debug.contains("max_write_chunk: Some(16)"), "{}", debug);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn pooled_output_matches_to_vec() {
+        use crate::to_vec_pooled;
+
+        let long = vec![7u32; 1000];
+        assert_eq!(to_vec_pooled(&long).unwrap(), to_vec(&long).unwrap());
+        let short = (1u8, "ab");
+        let out = to_vec_pooled(&short).unwrap();
+        assert_eq!(out, to_vec(&short).unwrap());
+        assert_eq!(out.capacity(), out.len());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn pooled_serialization_can_be_nested() {
+        use crate::to_vec_pooled;
+
+        struct Prefixed(u16);
+
+        impl Serialize for Prefixed {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                let inner = to_vec_pooled(&self.0).unwrap();
+                s.serialize_bytes(&inner)
+            }
+        }
+
+        let value = (Prefixed(0x0102), 3u8);
+        assert_eq!(to_vec_pooled(&value).unwrap(), [8, 0x02, 0x01, 3]);
+    }
+
+    #[test]
+    fn raw_bytes_are_spliced_verbatim() {
+        #[derive(Serialize)]
+        struct Record {
+            id: u16,
+            value: u32,
+            name: String,
+        }
+
+        let record = Record { id: 3, value: 0x0102_0304, name: "raw".into() };
+        let value = to_vec(&record.value).unwrap();
+        let mut serializer = Serializer::new(Vec::new());
+        record.id.serialize(&mut serializer).unwrap();
+        serializer.write_raw(&value).unwrap();
+        record.name.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.bytes_written(), 2 + 4 + 4);
+        assert_eq!(serializer.into_inner(), to_vec(&record).unwrap());
+    }
+
+    #[test]
+    fn to_writer_appends_to_borrowed_writer() {
+        let mut out = vec![0xff];
+        crate::to_writer(&mut out, &(1u16, "a")).unwrap();
+        crate::to_writer(&mut out, &true).unwrap();
+        assert_eq!(out, [0xff, 1, 0, 4, b'a', 1]);
+    }
+
+    #[test]
+    fn boxed_slice_matches_to_vec() {
+        let value = (vec![1u32, 2, 3], "boxed", Some(7u64));
+        let boxed = crate::to_boxed_slice(&value).unwrap();
+        assert_eq!(*boxed, *to_vec(&value).unwrap());
+        assert_eq!(boxed.len(), 1 + 12 + 1 + 5 + 1 + 8);
+    }
 }
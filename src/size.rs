@@ -0,0 +1,138 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{CountingWriter, Error, Serializer, Write};
+use core::{
+    convert::Infallible,
+    fmt::{self, Display},
+};
+use serde::Serialize;
+
+/// Returns the number of bytes a value serializes to
+///
+/// The value goes through the regular serializer, so compact length prefixes are accounted for,
+/// but no bytes are produced.
+///
+/// ```rust
+/// assert_eq!(serde_scale::serialized_size(&(1u16, "abc")).unwrap(), 2 + 1 + 3);
+/// assert_eq!(serde_scale::serialized_size(&[0u8; 64][..]).unwrap(), 2 + 64);
+/// ```
+pub fn serialized_size<T>(x: &T) -> Result<usize, Error<Infallible>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(CountingWriter::new());
+    x.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().count())
+}
+
+/// Checks that a value serializes to at most `max` bytes and returns its serialized size
+///
+/// No bytes are produced, and serialization stops as soon as the limit is exceeded, which allows
+/// rejecting oversized values early.
+///
+/// ```rust
+/// use serde_scale::{can_serialize, Error};
+///
+/// assert_eq!(can_serialize(&[1u32, 2], 8).unwrap(), 8);
+/// assert!(matches!(can_serialize(&[1u32, 2, 3], 8), Err(Error::Io(_))));
+/// ```
+pub fn can_serialize<T>(x: &T, max: usize) -> Result<usize, Error<SizeLimitExceeded>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(LimitedCounter { count: 0, max });
+    x.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().count)
+}
+
+/// Error indicating that a value serializes to more bytes than allowed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeLimitExceeded {
+    /// Maximum number of bytes allowed
+    pub limit: usize,
+}
+
+impl Display for SizeLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Serialized value exceeds the limit of {} bytes", self.limit)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SizeLimitExceeded {}
+
+/// Writer counting bytes and failing once they exceed a limit
+struct LimitedCounter {
+    count: usize,
+    max: usize,
+}
+
+impl Write for LimitedCounter {
+    type Error = SizeLimitExceeded;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.count = self.count
+            .checked_add(data.len())
+            .filter(|&n| n <= self.max)
+            .ok_or(SizeLimitExceeded { limit: self.max })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{can_serialize, Error, SizeLimitExceeded};
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn serialized_size_matches_to_vec() {
+        use crate::serialized_size;
+        use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+        fn check<T: serde::Serialize>(x: &T) {
+            assert_eq!(serialized_size(x).unwrap(), crate::to_vec(x).unwrap().len());
+        }
+
+        check(&());
+        check(&(true, 7u64, Some(3i16), None::<u8>));
+        check(&String::from("sized"));
+        check(&vec![0u8; 63]);
+        check(&vec![0u8; 64]);
+        check(&vec![0u32; 0x4000]);
+        check(&crate::Compact(u64::MAX));
+        check(&(0..70u16).map(|i| (i, vec![i; i.into()])).collect::<BTreeMap<_, _>>());
+        check(&Vec::<Option<bool>>::from([None, Some(true)]));
+    }
+
+    #[test]
+    fn value_within_limit_reports_its_size() {
+        assert_eq!(can_serialize(&(1u16, [0u8; 3]), 5).unwrap(), 5);
+        assert_eq!(can_serialize(&(1u16, [0u8; 3]), 100).unwrap(), 5);
+    }
+
+    #[test]
+    fn value_exceeding_limit_fails() {
+        assert!(matches!(
+            can_serialize(&(1u16, [0u8; 3]), 4),
+            Err(Error::Io(SizeLimitExceeded { limit: 4 })),
+        ));
+    }
+
+    #[test]
+    fn serialization_stops_at_limit() {
+        use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+        struct Endless;
+
+        impl Serialize for Endless {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                let mut seq = s.serialize_seq(Some(usize::MAX >> 2))?;
+                loop {
+                    seq.serialize_element(&0u64)?;
+                }
+            }
+        }
+
+        assert!(matches!(can_serialize(&Endless, 1000), Err(Error::Io(_))));
+    }
+}
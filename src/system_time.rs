@@ -0,0 +1,74 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `SystemTime` relative to the Unix epoch
+//!
+//! A time is encoded as the signed number of whole seconds since the Unix epoch as an `i64`
+//! followed by nanoseconds as a `u32`, i.e. 12 bytes. The nanoseconds are always added to the
+//! seconds, so a time 1.5 s before the epoch is encoded as -2 s and 500 000 000 ns. Deserialization
+//! fails if the nanoseconds are not below one billion or if the time cannot be represented.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use std::time::{Duration, SystemTime, UNIX_EPOCH};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Event {
+//!     #[serde(with = "serde_scale::system_time")]
+//!     at: SystemTime,
+//! }
+//!
+//! let event = Event { at: UNIX_EPOCH + Duration::new(1, 2) };
+//! let bytes = serde_scale::to_vec(&event).unwrap();
+//! assert_eq!(bytes, [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]);
+//! assert_eq!(serde_scale::from_slice::<Event>(&bytes).unwrap(), event);
+//! ```
+
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Serializes a time as seconds since the Unix epoch (`i64`) followed by nanoseconds (`u32`)
+pub fn serialize<S>(t: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let out_of_range = || S::Error::custom("Time is too far from the Unix epoch to be serialized");
+    let (secs, nanos) = match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => (i64::try_from(d.as_secs()).map_err(|_| out_of_range())?, d.subsec_nanos()),
+        Err(e) => {
+            let d = e.duration();
+            let secs = i64::try_from(d.as_secs()).map_err(|_| out_of_range())?;
+            match d.subsec_nanos() {
+                0 => (-secs, 0),
+                nanos => (
+                    (-secs).checked_sub(1).ok_or_else(out_of_range)?,
+                    NANOS_PER_SEC - nanos,
+                ),
+            }
+        }
+    };
+    (secs, nanos).serialize(serializer)
+}
+
+/// Deserializes a time from seconds since the Unix epoch (`i64`) followed by nanoseconds (`u32`)
+pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let (secs, nanos) = <(i64, u32)>::deserialize(deserializer)?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(D::Error::custom("Time nanoseconds must be less than one billion"));
+    }
+    let t = if secs >= 0 {
+        UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+    } else {
+        UNIX_EPOCH
+            .checked_sub(Duration::from_secs(secs.unsigned_abs()))
+            .and_then(|t| t.checked_add(Duration::from_nanos(nanos.into())))
+    };
+    t.ok_or_else(|| D::Error::custom("Time cannot be represented on this platform"))
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! Readers and writers recording how they are used, for assertions in tests
+
+use crate::{Bytes, Read, Write};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+/// Writer keeping the bytes of each call to `write` separately
+#[derive(Debug, Default)]
+pub(crate) struct RecordingWriter {
+    pub(crate) writes: Vec<Vec<u8>>,
+}
+
+impl RecordingWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all the bytes written so far
+    pub(crate) fn bytes(&self) -> Vec<u8> {
+        self.writes.concat()
+    }
+}
+
+impl Write for RecordingWriter {
+    type Error = Infallible;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.writes.push(data.to_vec());
+        Ok(())
+    }
+}
+
+/// Reader logging the number of bytes requested by each call to `read_map`
+#[derive(Debug)]
+pub(crate) struct RecordingReader<R> {
+    inner: R,
+    pub(crate) reads: Vec<usize>,
+}
+
+impl<R> RecordingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            reads: Vec::new(),
+        }
+    }
+}
+
+impl<'a, R: Read<'a>> Read<'a> for RecordingReader<R> {
+    type Error = R::Error;
+
+    fn read_map<T, F>(&mut self, n: usize, f: F) -> Result<T, Self::Error>
+    where
+        F: FnOnce(Bytes<'a, '_>) -> T,
+    {
+        self.reads.push(n);
+        self.inner.read_map(n, f)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingReader, RecordingWriter};
+    use crate::{to_vec, Deserializer, Serializer};
+    use alloc::{string::String, vec};
+    use serde::{Deserialize, Serialize, Serializer as _};
+
+    #[test]
+    fn byte_blob_is_written_in_one_call() {
+        let mut serializer = Serializer::new(RecordingWriter::new());
+        (&mut serializer).serialize_bytes(&[1, 2, 3, 4, 5]).unwrap();
+        let out = serializer.into_inner();
+        assert_eq!(out.writes, [vec![20], vec![1, 2, 3, 4, 5]]);
+    }
+
+    #[test]
+    fn string_is_read_in_one_call() {
+        let bytes = to_vec(&"hello").unwrap();
+        let mut deserializer = Deserializer::new(RecordingReader::new(&bytes[..]));
+        let s = String::deserialize(&mut deserializer).unwrap();
+        assert_eq!(s, "hello");
+        assert_eq!(deserializer.into_inner().reads, [1, 5]);
+    }
+
+    #[test]
+    fn recorded_bytes_match_to_vec() {
+        let value = (7u16, vec![1u32, 2]);
+        let mut serializer = Serializer::new(RecordingWriter::new());
+        value.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.into_inner().bytes(), to_vec(&value).unwrap());
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! (De)serialization of `Uuid` as 16 bytes
+//!
+//! The UUID is encoded as its 16 bytes in big-endian order as specified by RFC 4122, without a
+//! length prefix (i.e. like a `[u8; 16]`). This bypasses the default `serde` representation of
+//! `Uuid`, which depends on the serializer being human-readable.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use uuid::Uuid;
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Record {
+//!     #[serde(with = "serde_scale::uuid")]
+//!     id: Uuid,
+//! }
+//!
+//! let record = Record { id: Uuid::from_u128(0x0011_2233_4455_6677_8899_aabb_ccdd_eeff) };
+//! let bytes = serde_scale::to_vec(&record).unwrap();
+//! assert_eq!(bytes[0], 0x00);
+//! assert_eq!(bytes[15], 0xff);
+//! assert_eq!(serde_scale::from_slice::<Record>(&bytes).unwrap(), record);
+//! ```
+
+use ::uuid::Uuid;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes a UUID as 16 big-endian bytes
+pub fn serialize<S>(id: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    id.as_bytes().serialize(serializer)
+}
+
+/// Deserializes a UUID from 16 big-endian bytes
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    <[u8; 16]>::deserialize(deserializer).map(Uuid::from_bytes)
+}
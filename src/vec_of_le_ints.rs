@@ -0,0 +1,139 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+//! Bulk (de)serialization of `Vec<u32>` and `Vec<u64>`
+//!
+//! The encoding is identical to the one of a regular `Vec`. When deserializing with this crate's
+//! [`Deserializer`](crate::Deserializer), all elements are read at once instead of one at a time.
+//! On little-endian targets, the input is copied in one go without any per-element conversion,
+//! regardless of its alignment. Big-endian targets convert each element. Other deserializers fall
+//! back to the generic sequence path.
+//!
+//! ```rust
+//! # #[cfg(feature = "vec_of_le_ints")] {
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Samples {
+//!     #[serde(with = "serde_scale::vec_of_le_ints")]
+//!     values: Vec<u32>,
+//! }
+//!
+//! let samples = Samples { values: vec![1, 2, 3] };
+//! let bytes = serde_scale::to_vec(&samples).unwrap();
+//! assert_eq!(serde_scale::from_slice::<Samples>(&bytes).unwrap(), samples);
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Integer types supporting bulk deserialization
+pub trait LeInt: sealed::Sealed + Copy + Serialize + for<'de> Deserialize<'de> {}
+
+impl LeInt for u32 {}
+impl LeInt for u64 {}
+
+mod sealed {
+    use core::convert::TryInto;
+
+    pub trait Sealed: Sized {
+        const SIZE: usize;
+        const NAME: &'static str;
+
+        fn from_le_slice(b: &[u8]) -> Self;
+    }
+
+    impl Sealed for u32 {
+        const SIZE: usize = 4;
+        const NAME: &'static str = super::NAME_4;
+
+        fn from_le_slice(b: &[u8]) -> Self {
+            u32::from_le_bytes(b.try_into().unwrap())
+        }
+    }
+
+    impl Sealed for u64 {
+        const SIZE: usize = 8;
+        const NAME: &'static str = super::NAME_8;
+
+        fn from_le_slice(b: &[u8]) -> Self {
+            u64::from_le_bytes(b.try_into().unwrap())
+        }
+    }
+}
+
+const NAME_4: &str = "$serde_scale::vec_of_le_ints::4";
+const NAME_8: &str = "$serde_scale::vec_of_le_ints::8";
+
+/// Returns the element size if the newtype name requests bulk deserialization
+pub(crate) fn element_size(name: &str) -> Option<usize> {
+    match name {
+        NAME_4 => Some(4),
+        NAME_8 => Some(8),
+        _ => None,
+    }
+}
+
+/// Serializes integers like a regular `Vec`
+pub fn serialize<S, T>(v: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: LeInt,
+{
+    v.serialize(serializer)
+}
+
+/// Deserializes integers encoded like a regular `Vec`, reading them in bulk when possible
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: LeInt,
+{
+    deserializer.deserialize_newtype_struct(T::NAME, LeIntsVisitor(PhantomData))
+}
+
+struct LeIntsVisitor<T>(PhantomData<T>);
+
+impl<'de, T: LeInt> Visitor<'de> for LeIntsVisitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of integers")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(vec_from_le_bytes(v))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer)
+    }
+}
+
+fn vec_from_le_bytes<T: LeInt>(bytes: &[u8]) -> Vec<T> {
+    let n = bytes.len() / T::SIZE;
+    if cfg!(target_endian = "little") {
+        let mut v = Vec::<T>::with_capacity(n);
+        // SAFETY: `T` is a primitive integer for which any bit pattern is valid and whose
+        // in-memory representation is little-endian. The destination is a fresh allocation with
+        // room for `n` elements, so the input alignment does not matter.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                v.as_mut_ptr() as *mut u8,
+                n * T::SIZE,
+            );
+            v.set_len(n);
+        }
+        v
+    } else {
+        bytes.chunks_exact(T::SIZE).map(T::from_le_slice).collect()
+    }
+}
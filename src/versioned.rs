@@ -0,0 +1,125 @@
+// Copyright (C) 2020 Stephane Raux. Distributed under the zlib license.
+
+use crate::{Deserializer, Error, Read};
+use core::marker::PhantomData;
+use serde::Deserialize;
+
+/// Deserializes a value preceded by a version byte, dispatching on the version
+///
+/// The version is read when the `VersionedDeserializer` is created. Each call to
+/// [`on`](VersionedDeserializer::on) registers how to deserialize one version, and only the
+/// function matching the version read is called.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use serde_scale::{Deserializer, VersionedDeserializer};
+///
+/// #[derive(Deserialize)]
+/// struct V1 {
+///     x: u8,
+/// }
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct V2 {
+///     x: u8,
+///     y: u8,
+/// }
+///
+/// let input = [1, 7];
+/// let mut deserializer = Deserializer::new(&input[..]);
+/// let value = VersionedDeserializer::new(&mut deserializer)
+///     .unwrap()
+///     .on(1, |d| V1::deserialize(d).map(|v| V2 { x: v.x, y: 0 }))
+///     .on(2, |d| V2::deserialize(d))
+///     .finish()
+///     .unwrap();
+/// assert_eq!(value, V2 { x: 7, y: 0 });
+/// ```
+pub struct VersionedDeserializer<'a, 'de, R: Read<'de>, T> {
+    deserializer: &'a mut Deserializer<R>,
+    version: u8,
+    result: Option<Result<T, Error<R::Error>>>,
+    phantom: PhantomData<&'de ()>,
+}
+
+impl<'a, 'de, R: Read<'de>, T> VersionedDeserializer<'a, 'de, R, T> {
+    /// Reads the version byte
+    pub fn new(deserializer: &'a mut Deserializer<R>) -> Result<Self, Error<R::Error>> {
+        let version = u8::deserialize(&mut *deserializer)?;
+        Ok(Self {
+            deserializer,
+            version,
+            result: None,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the version read
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Deserializes the value with `f` if the version read is `version`
+    pub fn on<F>(mut self, version: u8, f: F) -> Self
+    where
+        F: FnOnce(&mut Deserializer<R>) -> Result<T, Error<R::Error>>,
+    {
+        if self.result.is_none() && self.version == version {
+            self.result = Some(f(self.deserializer));
+        }
+        self
+    }
+
+    /// Returns the deserialized value
+    ///
+    /// An error is returned if no function was registered for the version read.
+    pub fn finish(self) -> Result<T, Error<R::Error>> {
+        self.result.unwrap_or(Err(Error::UnknownVersion { found: self.version }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Deserializer, EndOfInput, Error, VersionedDeserializer};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct V1 {
+        x: u8,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct V2 {
+        x: u8,
+        y: u16,
+    }
+
+    impl From<V1> for V2 {
+        fn from(v: V1) -> Self {
+            V2 { x: v.x, y: 0 }
+        }
+    }
+
+    fn decode(input: &[u8]) -> Result<V2, Error<EndOfInput>> {
+        let mut deserializer = Deserializer::new(input);
+        VersionedDeserializer::new(&mut deserializer)?
+            .on(1, |d| V1::deserialize(d).map(Into::into))
+            .on(2, |d| V2::deserialize(d))
+            .finish()
+    }
+
+    #[test]
+    fn version_1_is_dispatched() {
+        assert_eq!(decode(&[1, 3]).unwrap(), V2 { x: 3, y: 0 });
+    }
+
+    #[test]
+    fn version_2_is_dispatched() {
+        assert_eq!(decode(&[2, 3, 4, 0]).unwrap(), V2 { x: 3, y: 4 });
+    }
+
+    #[test]
+    fn unknown_version_fails() {
+        assert!(matches!(decode(&[3, 3]), Err(Error::UnknownVersion { found: 3 })));
+    }
+}
@@ -2,7 +2,10 @@
 
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
-use core::fmt::{Debug, Display};
+use core::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+};
 
 /// Interface to write bytes
 pub trait Write {
@@ -29,3 +32,239 @@ impl Write for Vec<u8> {
         Ok(())
     }
 }
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> Write for heapless::Vec<u8, N> {
+    type Error = BufferOverflow;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(data).map_err(|_| BufferOverflow)
+    }
+}
+
+/// Writer filling a mutable slice
+///
+/// Writing more bytes than the slice can hold fails with [`BufferOverflow`], and the bytes of the
+/// failed write are not written. No allocation is needed, so this works without `alloc`.
+///
+/// ```rust
+/// use serde_scale::SliceWriter;
+///
+/// let mut buf = [0u8; 16];
+/// let mut writer = SliceWriter::new(&mut buf);
+/// serde_scale::to_writer(&mut writer, &(1u16, "one")).unwrap();
+/// assert_eq!(writer.bytes_written(), 6);
+/// assert_eq!(writer.into_remaining().len(), 10);
+/// assert_eq!(buf[..6], [1, 0, 12, b'o', b'n', b'e']);
+/// ```
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Returns a writer filling `buf` from its start
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Returns the number of bytes written so far
+    pub fn bytes_written(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the part of the slice that was not written to
+    pub fn into_remaining(self) -> &'a mut [u8] {
+        &mut self.buf[self.pos..]
+    }
+}
+
+impl Write for SliceWriter<'_> {
+    type Error = BufferOverflow;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let dst = self.buf
+            .get_mut(self.pos..self.pos + data.len())
+            .ok_or(BufferOverflow)?;
+        dst.copy_from_slice(data);
+        self.pos += data.len();
+        Ok(())
+    }
+}
+
+/// Writer discarding bytes and only counting them
+///
+/// This allows dry-run serialization, e.g. to reserve capacity or check a size limit before
+/// producing any bytes. [`serialized_size`](crate::serialized_size) is built on it.
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serde_scale::{CountingWriter, Serializer};
+///
+/// let mut serializer = Serializer::new(CountingWriter::new());
+/// (1u32, "four").serialize(&mut serializer).unwrap();
+/// assert_eq!(serializer.into_inner().count(), 9);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CountingWriter {
+    count: usize,
+}
+
+impl CountingWriter {
+    /// Returns a writer that has counted no bytes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes written so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Write for CountingWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.count += data.len();
+        Ok(())
+    }
+}
+
+/// Error indicating that a fixed-capacity output is too small for the bytes written to it
+///
+/// Bytes that do not fit are not written, so the output holds a prefix of the serialized value.
+#[derive(Debug)]
+pub struct BufferOverflow;
+
+impl Display for BufferOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BufferOverflow")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferOverflow {}
+
+/// Writer forwarding bytes to a closure
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use serde::Serialize;
+/// use serde_scale::{ClosureWriter, Serializer};
+///
+/// let mut out = Vec::new();
+/// let writer = ClosureWriter::new(|data: &[u8]| {
+///     out.extend_from_slice(data);
+///     Ok::<_, core::convert::Infallible>(())
+/// });
+/// 3u16.serialize(&mut Serializer::new(writer)).unwrap();
+/// assert_eq!(out, [3, 0]);
+/// # }
+/// ```
+pub struct ClosureWriter<F, E> {
+    f: F,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<F, E> ClosureWriter<F, E>
+where
+    F: FnMut(&[u8]) -> Result<(), E>,
+    E: Debug + Display,
+{
+    /// Returns a writer calling `f` with the bytes to write
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            error: PhantomData,
+        }
+    }
+
+    /// Returns the underlying closure
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F, E> Debug for ClosureWriter<F, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClosureWriter").finish_non_exhaustive()
+    }
+}
+
+impl<F, E> Write for ClosureWriter<F, E>
+where
+    F: FnMut(&[u8]) -> Result<(), E>,
+    E: Debug + Display,
+{
+    type Error = E;
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        (self.f)(data)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{
+        to_vec, BufferOverflow, ClosureWriter, CountingWriter, Error, Serializer, SliceWriter,
+    };
+    use alloc::{string::String, vec, vec::Vec};
+    use core::convert::Infallible;
+    use serde::Serialize;
+
+    #[test]
+    fn closure_writer_output_matches_to_vec() {
+        let value = (vec![1u32, 2], String::from("abc"), Some(7i16));
+        let mut out = Vec::new();
+        let writer = ClosureWriter::new(|data: &[u8]| {
+            out.extend_from_slice(data);
+            Ok::<_, Infallible>(())
+        });
+        value.serialize(&mut Serializer::new(writer)).unwrap();
+        assert_eq!(out, to_vec(&value).unwrap());
+    }
+
+    #[test]
+    fn closure_writer_errors_are_reported() {
+        let writer = ClosureWriter::new(|_: &[u8]| Err("full"));
+        let res = 1u8.serialize(&mut Serializer::new(writer));
+        assert!(matches!(res, Err(Error::Io("full"))));
+    }
+
+    #[test]
+    fn slice_writer_output_matches_to_vec() {
+        let value = (vec![1u32, 2], String::from("abc"), Some(7i16));
+        let mut buf = [0xaa; 32];
+        let mut serializer = Serializer::new(SliceWriter::new(&mut buf));
+        value.serialize(&mut serializer).unwrap();
+        let writer = serializer.into_inner();
+        let n = writer.bytes_written();
+        assert_eq!(writer.into_remaining(), [0xaa; 32 - 16]);
+        assert_eq!(buf[..n], to_vec(&value).unwrap()[..]);
+    }
+
+    #[test]
+    fn slice_writer_fails_when_full() {
+        let mut buf = [0; 5];
+        let mut serializer = Serializer::new(SliceWriter::new(&mut buf));
+        let res = (1u16, 2u32).serialize(&mut serializer);
+        assert!(matches!(res, Err(Error::Io(BufferOverflow))));
+        let writer = serializer.into_inner();
+        assert_eq!(writer.bytes_written(), 2);
+        assert_eq!(writer.into_remaining().len(), 3);
+    }
+
+    #[test]
+    fn counted_size_allows_exact_reservation() {
+        let value = (vec![String::from("a"); 100], 5u64);
+        let mut counter = CountingWriter::new();
+        value.serialize(&mut Serializer::new(&mut counter)).unwrap();
+        let mut out = Vec::with_capacity(counter.count());
+        let capacity = out.capacity();
+        value.serialize(&mut Serializer::new(&mut out)).unwrap();
+        assert_eq!(out.len(), counter.count());
+        assert_eq!(out.capacity(), capacity);
+    }
+}